@@ -0,0 +1,327 @@
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+
+use thiserror::Error;
+
+/// Errors a client call can fail with.
+///
+/// Distinct from `kv_data::errors::Errors`: that type describes failures
+/// inside the storage engine, while this one describes failures in getting
+/// a request to (and a response back from) the server over HTTP.
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("request failed: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("server returned {0}")]
+    Status(reqwest::StatusCode),
+    #[error("key is not exist")]
+    KeyIsNotExist,
+}
+
+type Result<T> = std::result::Result<T, ClientError>;
+
+/// How the blocking client retries a request that failed transiently
+/// (connection refused, timeout, or a 5xx response).
+///
+/// Every attempt re-issues the request (including the body) from scratch,
+/// waiting `base_delay * 2^attempt` between tries, capped at `max_delay`.
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(50),
+            max_delay: Duration::from_secs(2),
+        }
+    }
+}
+
+impl RetryPolicy {
+    fn delay_for(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(1 << attempt.min(16))
+            .min(self.max_delay)
+    }
+}
+
+/// Blocking client surface over the `/bitcask` routes.
+///
+/// Transient failures (connection refused, a 5xx response) are retried with
+/// bounded exponential backoff per [`RetryPolicy`]; a non-transient failure
+/// (e.g. a 404 on `get`) is returned immediately.
+pub trait SyncClient {
+    fn put(&self, entries: HashMap<String, String>) -> Result<()>;
+    fn get(&self, key: &str) -> Result<String>;
+    fn delete(&self, key: &str) -> Result<()>;
+    fn list_keys(&self) -> Result<Vec<String>>;
+    fn stat(&self) -> Result<HashMap<String, u64>>;
+}
+
+/// Async client surface over the `/bitcask` routes.
+///
+/// Each call fires its request once: no retry, no waiting. Callers that
+/// want retry semantics over the async client build it themselves at the
+/// call site, the same way they would for any other async HTTP call.
+pub trait AsyncClient {
+    fn put(
+        &self,
+        entries: HashMap<String, String>,
+    ) -> impl std::future::Future<Output = Result<()>> + Send;
+    fn get(&self, key: &str) -> impl std::future::Future<Output = Result<String>> + Send;
+    fn delete(&self, key: &str) -> impl std::future::Future<Output = Result<()>> + Send;
+    fn list_keys(&self) -> impl std::future::Future<Output = Result<Vec<String>>> + Send;
+    fn stat(&self) -> impl std::future::Future<Output = Result<HashMap<String, u64>>> + Send;
+}
+
+/// Concrete client for the `/bitcask` HTTP routes, implementing both
+/// [`SyncClient`] and [`AsyncClient`] over a shared base URL.
+pub struct HttpClient {
+    base_url: String,
+    retry: RetryPolicy,
+    blocking: reqwest::blocking::Client,
+    r#async: reqwest::Client,
+}
+
+impl HttpClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_retry_policy(base_url, RetryPolicy::default())
+    }
+
+    pub fn with_retry_policy(base_url: impl Into<String>, retry: RetryPolicy) -> Self {
+        Self {
+            base_url: base_url.into(),
+            retry,
+            blocking: reqwest::blocking::Client::new(),
+            r#async: reqwest::Client::new(),
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        std::format!("{}/bitcask{}", self.base_url, path)
+    }
+
+    /// Run `send` until it succeeds, the error looks permanent, or
+    /// `retry.max_attempts` is reached, sleeping with exponential backoff
+    /// in between.
+    fn with_retry(
+        &self,
+        mut send: impl FnMut() -> reqwest::Result<reqwest::blocking::Response>,
+    ) -> Result<reqwest::blocking::Response> {
+        let mut last_err = None;
+        let max_attempts = self.retry.max_attempts.max(1);
+        for attempt in 0..max_attempts {
+            match send() {
+                Ok(resp) if !resp.status().is_server_error() => return Ok(resp),
+                Ok(resp) => last_err = Some(ClientError::Status(resp.status())),
+                Err(e) if e.is_connect() || e.is_timeout() => {
+                    last_err = Some(ClientError::Request(e))
+                }
+                Err(e) => return Err(ClientError::Request(e)),
+            }
+            if attempt + 1 < max_attempts {
+                thread::sleep(self.retry.delay_for(attempt));
+            }
+        }
+        Err(last_err.expect("loop runs at least once since max_attempts >= 1"))
+    }
+}
+
+impl SyncClient for HttpClient {
+    fn put(&self, entries: HashMap<String, String>) -> Result<()> {
+        let resp = self.with_retry(|| {
+            self.blocking.post(self.url("/put")).json(&entries).send()
+        })?;
+        if resp.status().is_success() {
+            Ok(())
+        } else {
+            Err(ClientError::Status(resp.status()))
+        }
+    }
+
+    fn get(&self, key: &str) -> Result<String> {
+        let url = self.url(&std::format!("/get/{}", key));
+        let resp = self.with_retry(|| self.blocking.get(&url).send())?;
+        match resp.status() {
+            status if status.is_success() => Ok(resp.text()?),
+            reqwest::StatusCode::NOT_FOUND => Err(ClientError::KeyIsNotExist),
+            status => Err(ClientError::Status(status)),
+        }
+    }
+
+    fn delete(&self, key: &str) -> Result<()> {
+        let url = self.url(&std::format!("/delete/{}", key));
+        let resp = self.with_retry(|| self.blocking.delete(&url).send())?;
+        if resp.status().is_success() {
+            Ok(())
+        } else {
+            Err(ClientError::Status(resp.status()))
+        }
+    }
+
+    fn list_keys(&self) -> Result<Vec<String>> {
+        let resp = self.with_retry(|| self.blocking.get(self.url("/listkeys")).send())?;
+        if resp.status().is_success() {
+            Ok(resp.json()?)
+        } else {
+            Err(ClientError::Status(resp.status()))
+        }
+    }
+
+    fn stat(&self) -> Result<HashMap<String, u64>> {
+        let resp = self.with_retry(|| self.blocking.get(self.url("/stat")).send())?;
+        if resp.status().is_success() {
+            Ok(resp.json()?)
+        } else {
+            Err(ClientError::Status(resp.status()))
+        }
+    }
+}
+
+impl AsyncClient for HttpClient {
+    async fn put(&self, entries: HashMap<String, String>) -> Result<()> {
+        let resp = self
+            .r#async
+            .post(self.url("/put"))
+            .json(&entries)
+            .send()
+            .await?;
+        if resp.status().is_success() {
+            Ok(())
+        } else {
+            Err(ClientError::Status(resp.status()))
+        }
+    }
+
+    async fn get(&self, key: &str) -> Result<String> {
+        let resp = self
+            .r#async
+            .get(self.url(&std::format!("/get/{}", key)))
+            .send()
+            .await?;
+        match resp.status() {
+            status if status.is_success() => Ok(resp.text().await?),
+            reqwest::StatusCode::NOT_FOUND => Err(ClientError::KeyIsNotExist),
+            status => Err(ClientError::Status(status)),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let resp = self
+            .r#async
+            .delete(self.url(&std::format!("/delete/{}", key)))
+            .send()
+            .await?;
+        if resp.status().is_success() {
+            Ok(())
+        } else {
+            Err(ClientError::Status(resp.status()))
+        }
+    }
+
+    async fn list_keys(&self) -> Result<Vec<String>> {
+        let resp = self.r#async.get(self.url("/listkeys")).send().await?;
+        if resp.status().is_success() {
+            Ok(resp.json().await?)
+        } else {
+            Err(ClientError::Status(resp.status()))
+        }
+    }
+
+    async fn stat(&self) -> Result<HashMap<String, u64>> {
+        let resp = self.r#async.get(self.url("/stat")).send().await?;
+        if resp.status().is_success() {
+            Ok(resp.json().await?)
+        } else {
+            Err(ClientError::Status(resp.status()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{delete_handler, get_handler, listkeys_handler, put_handler, stat_handler};
+    use actix_web::web::Bytes;
+    use actix_web::{web, App, HttpServer, Scope};
+    use kv_data::db::Engine;
+    use kv_data::options::Options;
+    use std::net::TcpListener;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    /// Bind on an ephemeral port and start the `/bitcask` service in the
+    /// background, returning the base URL it's reachable at.
+    async fn spawn_server(dir_name: &str) -> String {
+        let mut opts = Options::default();
+        opts.dir_path = PathBuf::from(std::format!("/tmp/bitcask-rs-http-client-{}", dir_name));
+        let engine = Arc::new(Engine::open(opts).expect("failed to open engine"));
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind ephemeral port");
+        let addr = listener.local_addr().unwrap();
+
+        let server = HttpServer::new(move || {
+            App::new().app_data(web::Data::new(engine.clone())).service(
+                Scope::new("/bitcask")
+                    .service(put_handler)
+                    .service(get_handler)
+                    .service(delete_handler)
+                    .service(listkeys_handler)
+                    .service(stat_handler),
+            )
+        })
+        .listen(listener)
+        .expect("failed to bind actix server")
+        .run();
+        tokio::spawn(server);
+
+        std::format!("http://{}", addr)
+    }
+
+    #[actix_web::test]
+    async fn test_sync_client_round_trip() {
+        let base_url = spawn_server("sync").await;
+        let client = HttpClient::new(base_url);
+
+        client
+            .put(HashMap::from([("aacc".to_string(), "hello".to_string())]))
+            .expect("put failed");
+        assert_eq!(client.get("aacc").expect("get failed"), "hello");
+        assert!(client.list_keys().expect("list_keys failed").contains(&"aacc".to_string()));
+        assert!(client.stat().expect("stat failed").contains_key("key_num"));
+        client.delete("aacc").expect("delete failed");
+        assert!(matches!(client.get("aacc"), Err(ClientError::KeyIsNotExist)));
+    }
+
+    #[actix_web::test]
+    async fn test_async_client_round_trip() {
+        let base_url = spawn_server("async").await;
+        let client = HttpClient::new(base_url);
+
+        AsyncClient::put(
+            &client,
+            HashMap::from([("eecc".to_string(), "world".to_string())]),
+        )
+        .await
+        .expect("put failed");
+        assert_eq!(
+            AsyncClient::get(&client, "eecc").await.expect("get failed"),
+            "world"
+        );
+        assert!(AsyncClient::list_keys(&client)
+            .await
+            .expect("list_keys failed")
+            .contains(&"eecc".to_string()));
+        AsyncClient::delete(&client, "eecc").await.expect("delete failed");
+        assert!(matches!(
+            AsyncClient::get(&client, "eecc").await,
+            Err(ClientError::KeyIsNotExist)
+        ));
+    }
+}
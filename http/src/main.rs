@@ -1,10 +1,17 @@
+mod client;
+
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::pin::Pin;
 use std::sync::Arc;
-use actix_web::{post, web, App, HttpResponse, HttpServer, Responder, Scope};
+use std::task::{Context, Poll};
+use actix_web::{delete, get, post, web, App, HttpResponse, HttpServer, Responder, Scope};
 use actix_web::web::{scope, Bytes};
+use futures_core::Stream;
 use kv_data::db::Engine;
-use kv_data::options::Options;
+use kv_data::iterator::OwnedIterator;
+use kv_data::options::{IteratorOptions, Options};
+use serde::Deserialize;
 
 #[post("/put")]
 async fn put_handler(
@@ -20,6 +27,229 @@ async fn put_handler(
     HttpResponse::Ok().body("OK")
 }
 
+#[get("/get/{key}")]
+async fn get_handler(eng: web::Data<Arc<Engine>>, key: web::Path<String>) -> impl Responder {
+    match eng.get(Bytes::from(key.into_inner())) {
+        Ok(value) => HttpResponse::Ok().body(value.to_vec()),
+        Err(_) => HttpResponse::NotFound().body("key not found"),
+    }
+}
+
+#[delete("/delete/{key}")]
+async fn delete_handler(eng: web::Data<Arc<Engine>>, key: web::Path<String>) -> impl Responder {
+    match eng.delete(Bytes::from(key.into_inner())) {
+        Ok(_) => HttpResponse::Ok().body("OK"),
+        Err(_) => HttpResponse::InternalServerError().body("failed to delete key"),
+    }
+}
+
+#[get("/listkeys")]
+async fn listkeys_handler(eng: web::Data<Arc<Engine>>) -> impl Responder {
+    match eng.list_keys() {
+        Ok(keys) => {
+            let keys: Vec<String> = keys
+                .iter()
+                .map(|k| String::from_utf8_lossy(k).to_string())
+                .collect();
+            HttpResponse::Ok().json(keys)
+        }
+        Err(_) => HttpResponse::InternalServerError().body("failed to list keys"),
+    }
+}
+
+#[get("/stat")]
+async fn stat_handler(eng: web::Data<Arc<Engine>>) -> impl Responder {
+    match eng.stat() {
+        Ok(stat) => HttpResponse::Ok().json(HashMap::from([
+            ("key_num".to_string(), stat.key_num() as u64),
+            ("data_file_num".to_string(), stat.data_file_num() as u64),
+            ("reclaim_size".to_string(), stat.reclaim_size() as u64),
+            ("disk_size".to_string(), stat.disk_size()),
+        ])),
+        Err(_) => HttpResponse::InternalServerError().body("failed to read engine stat"),
+    }
+}
+
+/// Decode a hex string into bytes, rejecting anything of odd length or with
+/// non-hex digits rather than panicking on a malformed query parameter.
+fn decode_hex(s: &str) -> std::result::Result<Vec<u8>, ()> {
+    if s.len() % 2 != 0 {
+        return Err(());
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).map_err(|_| ()))
+        .collect()
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| std::format!("{:02x}", b)).collect()
+}
+
+fn default_scan_limit() -> usize {
+    100
+}
+
+#[derive(Deserialize)]
+struct ScanQuery {
+    #[serde(default)]
+    prefix: Option<String>,
+    #[serde(default)]
+    reverse: bool,
+    #[serde(default)]
+    start: Option<String>,
+    #[serde(default = "default_scan_limit")]
+    limit: usize,
+}
+
+#[derive(Deserialize)]
+struct KeysQuery {
+    #[serde(default)]
+    prefix: Option<String>,
+}
+
+enum ScanStreamState {
+    Head,
+    Items,
+    Tail,
+    Done,
+}
+
+/// Drives `scan_handler`'s chunked response: each `poll_next` call pulls at
+/// most one entry off the index iterator and renders it as one JSON-array
+/// element, so the response body is produced incrementally instead of
+/// buffering the whole (bounded) result set before the first byte is sent.
+/// Holds an `OwnedIterator` rather than `kv_data::iterator::Iterator`
+/// because the stream must be `'static` to satisfy `HttpResponse::streaming`.
+struct ScanStream {
+    iter: OwnedIterator,
+    limit: usize,
+    emitted: usize,
+    state: ScanStreamState,
+}
+
+impl Stream for ScanStream {
+    type Item = std::result::Result<Bytes, std::io::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match this.state {
+                ScanStreamState::Head => {
+                    this.state = ScanStreamState::Items;
+                    return Poll::Ready(Some(Ok(Bytes::from_static(b"["))));
+                }
+                ScanStreamState::Items => {
+                    if this.emitted >= this.limit {
+                        this.state = ScanStreamState::Tail;
+                        continue;
+                    }
+                    match this.iter.try_next() {
+                        Ok(Some((key, value))) => {
+                            let sep = if this.emitted == 0 { "" } else { "," };
+                            this.emitted += 1;
+                            let chunk = std::format!(
+                                "{}{{\"key\":\"{}\",\"value\":\"{}\"}}",
+                                sep,
+                                encode_hex(&key),
+                                encode_hex(&value)
+                            );
+                            return Poll::Ready(Some(Ok(Bytes::from(chunk))));
+                        }
+                        Ok(None) => {
+                            this.state = ScanStreamState::Tail;
+                            continue;
+                        }
+                        Err(_) => {
+                            this.state = ScanStreamState::Done;
+                            return Poll::Ready(Some(Err(std::io::Error::new(
+                                std::io::ErrorKind::Other,
+                                "failed to read value during scan",
+                            ))));
+                        }
+                    }
+                }
+                ScanStreamState::Tail => {
+                    this.state = ScanStreamState::Done;
+                    return Poll::Ready(Some(Ok(Bytes::from_static(b"]"))));
+                }
+                ScanStreamState::Done => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+/// Range/prefix scan over the engine's index, driven by the same
+/// `IteratorOptions` used internally (see `kv_data::iterator`). Keys and
+/// values are hex-encoded since JSON strings must be valid UTF-8 and values
+/// stored through this API are arbitrary bytes.
+///
+/// The response body is a chunked transfer (`HttpResponse::streaming`) over
+/// `ScanStream`, so a large (but still `limit`-bounded) scan doesn't have to
+/// sit fully in memory before the first chunk goes out.
+#[get("/scan")]
+async fn scan_handler(eng: web::Data<Arc<Engine>>, query: web::Query<ScanQuery>) -> impl Responder {
+    let prefix = match query.prefix.as_deref().map(decode_hex).transpose() {
+        Ok(prefix) => prefix.unwrap_or_default(),
+        Err(_) => return HttpResponse::BadRequest().body("prefix must be hex-encoded"),
+    };
+    let start = match query.start.as_deref().map(decode_hex).transpose() {
+        Ok(start) => start,
+        Err(_) => return HttpResponse::BadRequest().body("start must be hex-encoded"),
+    };
+
+    let mut iter_opts = IteratorOptions::default();
+    iter_opts.prefix = prefix;
+    iter_opts.reverse = query.reverse;
+    let mut iter = eng.get_ref().clone().iter_owned(iter_opts);
+    if let Some(start) = start {
+        iter.seek(start);
+    }
+
+    let stream = ScanStream {
+        iter,
+        limit: query.limit,
+        emitted: 0,
+        state: ScanStreamState::Head,
+    };
+
+    HttpResponse::Ok()
+        .content_type("application/json")
+        .streaming(stream)
+}
+
+/// Prefix listing over the engine's index, using the same prefix-filtering
+/// path as `scan_handler` but returning hex-encoded keys only.
+#[get("/keys")]
+async fn keys_handler(eng: web::Data<Arc<Engine>>, query: web::Query<KeysQuery>) -> impl Responder {
+    let prefix = match query.prefix.as_deref().map(decode_hex).transpose() {
+        Ok(prefix) => prefix.unwrap_or_default(),
+        Err(_) => return HttpResponse::BadRequest().body("prefix must be hex-encoded"),
+    };
+
+    let mut iter_opts = IteratorOptions::default();
+    iter_opts.prefix = prefix;
+    let mut iter = eng.iter(iter_opts);
+
+    let mut keys = Vec::new();
+    loop {
+        match iter.try_next() {
+            Ok(Some((key, _))) => keys.push(encode_hex(&key)),
+            Ok(None) => break,
+            Err(_) => return HttpResponse::InternalServerError().body("failed to list keys"),
+        }
+    }
+
+    HttpResponse::Ok().json(keys)
+}
+
+#[get("/index.dot")]
+async fn index_dot_handler(eng: web::Data<Arc<Engine>>) -> impl Responder {
+    HttpResponse::Ok()
+        .content_type("text/vnd.graphviz")
+        .body(eng.dump_index_dot())
+}
+
 #[actix_web::main]
 async fn main()->std::io::Result<()> {
     let mut opts = Options::default();
@@ -34,7 +264,10 @@ async fn main()->std::io::Result<()> {
                 .service(get_handler)
                 .service(delete_handler)
                 .service(listkeys_handler)
-                .service(stat_handler),
+                .service(stat_handler)
+                .service(scan_handler)
+                .service(keys_handler)
+                .service(index_dot_handler),
         )
     })
         .bind(("127.0.0.1", 8080))?
@@ -0,0 +1,65 @@
+use crate::db::Engine;
+use crate::errors::{Errors, Result};
+use bytes::Bytes;
+use std::sync::Arc;
+
+/// An async handle over the synchronous [`Engine`].
+///
+/// `Engine`'s IO is blocking (`pread`/`write`/`fsync`), so every call here
+/// is offloaded to `tokio::task::spawn_blocking` rather than run directly on
+/// the async executor's worker threads; this keeps a slow disk from
+/// stalling unrelated tasks.
+pub struct AsyncEngine {
+    inner: Arc<Engine>,
+}
+
+impl AsyncEngine {
+    pub fn new(engine: Engine) -> Self {
+        AsyncEngine {
+            inner: Arc::new(engine),
+        }
+    }
+
+    pub async fn put(&self, key: Bytes, value: Bytes) -> Result<()> {
+        let engine = self.inner.clone();
+        run_blocking(move || engine.put(key, value)).await
+    }
+
+    pub async fn get(&self, key: Bytes) -> Result<Bytes> {
+        let engine = self.inner.clone();
+        run_blocking(move || engine.get(key)).await
+    }
+
+    pub async fn delete(&self, key: Bytes) -> Result<()> {
+        let engine = self.inner.clone();
+        run_blocking(move || engine.delete(key)).await
+    }
+
+    pub async fn sync(&self) -> Result<()> {
+        let engine = self.inner.clone();
+        run_blocking(move || engine.sync()).await
+    }
+
+    pub async fn list_keys(&self) -> Result<Vec<Bytes>> {
+        let engine = self.inner.clone();
+        run_blocking(move || engine.list_keys()).await
+    }
+
+    pub async fn merge(&self) -> Result<()> {
+        let engine = self.inner.clone();
+        run_blocking(move || engine.merge()).await
+    }
+}
+
+/// Run a blocking `Engine` call on the blocking thread pool and flatten the
+/// `JoinError` a panicked task would otherwise surface as.
+async fn run_blocking<F, T>(f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(_) => Err(Errors::AsyncTaskPanicked),
+    }
+}
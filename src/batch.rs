@@ -1,10 +1,10 @@
-use crate::data::log_record::{LogRecodType, LogRecord};
+use crate::data::log_record::{LogRecodPos, LogRecodType, LogRecord};
 use crate::db::Engine;
 use crate::errors::{Errors, Result};
 use crate::options::IndexType::BPlusTree;
 use crate::options::WriteBatchOptions;
 use bytes::{BufMut, Bytes, BytesMut};
-use parking_lot::Mutex;
+use parking_lot::{Condvar, Mutex};
 use prost::encoding::bool::encode;
 use prost::{decode_length_delimiter, encode_length_delimiter, Message};
 use std::collections::HashMap;
@@ -14,6 +14,24 @@ use std::sync::Arc;
 const TXN_FIN_KEY: &[u8] = "txn-fin".as_bytes();
 pub(crate) const NON_TRANSACTION_SEQ_NO: usize = 0;
 
+/// One batch's worth of already-encoded records, queued for group commit.
+///
+/// `buf` is every record in the batch (each rewritten with the batch's
+/// sequence number, terminated by the `TXN_FIN_KEY` record) concatenated
+/// into a single contiguous buffer, so the leader can write it with one
+/// `DataFile::write` call. `records` lists, in the same order they appear
+/// in `buf`, each record's encoded length and — for the ones that should
+/// update the index (i.e. not the transaction-finished marker) — its key,
+/// so the leader can derive every `LogRecodPos` from the base offset it
+/// wrote `buf` at plus the running sum of preceding lengths.
+pub(crate) struct PendingCommit {
+    pub(crate) buf: Vec<u8>,
+    pub(crate) records: Vec<(Option<Vec<u8>>, usize)>,
+    pub(crate) want_sync: bool,
+    pub(crate) result: Mutex<Option<Result<Vec<(Vec<u8>, LogRecodPos)>>>>,
+    pub(crate) cv: Condvar,
+}
+
 pub struct WriteBatch<'a> {
     pending_writes: Arc<Mutex<HashMap<Vec<u8>, LogRecord>>>,
     engine: &'a Engine,
@@ -77,34 +95,56 @@ impl WriteBatch<'_> {
         if pending_writes.len() > self.options.max_batch_num {
             return Err(Errors::ExceedMaxBatchNum);
         }
-        let lock_ = self.engine.batch_commit_lock.lock();
-        let mut seq_no = self.engine.seq_no.fetch_add(1, Ordering::SeqCst);
-        let mut positions = HashMap::new();
+        let seq_no = self.engine.seq_no.fetch_add(1, Ordering::SeqCst);
+
+        // Encode every pending record plus the transaction-finished marker
+        // into one contiguous buffer up front, so the leader that ends up
+        // writing this batch only needs a single `DataFile::write` call for
+        // it, instead of one call per record.
+        let mut buf = Vec::new();
+        let mut records = Vec::with_capacity(pending_writes.len() + 1);
         for (_, item) in pending_writes.iter() {
             let mut record = LogRecord {
                 key: log_record_with_seq(item.key.clone(), seq_no),
                 value: item.value.clone(),
                 rec_type: item.rec_type,
             };
-            let pos = self.engine.append_log_record(&mut record)?;
-            positions.insert(item.key.clone(), pos);
+            if record.rec_type == LogRecodType::NORMAL {
+                record.value = crate::data::compress::encode_value(
+                    &record.value,
+                    self.engine.option.compression,
+                );
+            }
+            let enc = record.encode();
+            records.push((Some(item.key.clone()), enc.len()));
+            buf.extend_from_slice(&enc);
         }
         let mut finish_record = LogRecord {
             key: TXN_FIN_KEY.to_vec(),
             value: Default::default(),
             rec_type: LogRecodType::TXNFINSHED,
         };
-        self.engine.append_log_record(&mut finish_record)?;
-        if self.options.sync_writes {
-            self.engine.sync()?;
-        }
+        let finish_enc = finish_record.encode();
+        records.push((None, finish_enc.len()));
+        buf.extend_from_slice(&finish_enc);
+
+        let job = Arc::new(PendingCommit {
+            buf,
+            records,
+            want_sync: self.options.sync_writes,
+            result: Mutex::new(None),
+            cv: Condvar::new(),
+        });
+        let positions: HashMap<Vec<u8>, LogRecodPos> =
+            self.engine.group_commit(job)?.into_iter().collect();
 
         for (_, item) in pending_writes.iter() {
-            if item.rec_type == LogRecodType::NORMAL {
-                let record_pos = positions.get(&item.key).unwrap();
-                self.engine.index.put(item.key.clone(), *record_pos);
-            } else if item.rec_type == LogRecodType::DELETED {
-                self.engine.index.delete(item.key.clone());
+            if item.rec_type == LogRecodType::NORMAL || item.rec_type == LogRecodType::DELETED {
+                let record_pos = *positions.get(&item.key).unwrap();
+                self.engine.update_index(item.key.clone(), item.rec_type, record_pos);
+                if item.rec_type == LogRecodType::NORMAL {
+                    self.engine.bloom.read().insert(&item.key);
+                }
             }
         }
         pending_writes.clear();
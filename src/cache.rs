@@ -0,0 +1,166 @@
+use bytes::Bytes;
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+const SHARD_COUNT: usize = 16;
+
+/// Hit/miss counters for the read cache, exposed so embedders can monitor
+/// how effective a given `cache_capacity_bytes` setting is.
+#[derive(Default)]
+pub struct CacheStats {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl CacheStats {
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+}
+
+type CacheKey = (u32, u64);
+
+struct Shard {
+    entries: HashMap<CacheKey, Bytes>,
+    order: VecDeque<CacheKey>,
+    bytes: usize,
+}
+
+impl Shard {
+    fn new() -> Self {
+        Shard {
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            bytes: 0,
+        }
+    }
+
+    fn get(&mut self, key: &CacheKey) -> Option<Bytes> {
+        if let Some(value) = self.entries.get(key) {
+            let value = value.clone();
+            self.touch(key);
+            return Some(value);
+        }
+        None
+    }
+
+    fn touch(&mut self, key: &CacheKey) {
+        if let Some(pos) = self.order.iter().position(|k| k == key) {
+            self.order.remove(pos);
+        }
+        self.order.push_back(*key);
+    }
+
+    fn put(&mut self, key: CacheKey, value: Bytes, capacity: usize) {
+        if let Some(old) = self.entries.insert(key, value.clone()) {
+            self.bytes -= old.len();
+        }
+        self.bytes += value.len();
+        self.touch(&key);
+
+        while self.bytes > capacity {
+            match self.order.pop_front() {
+                Some(evict_key) => {
+                    if let Some(evicted) = self.entries.remove(&evict_key) {
+                        self.bytes -= evicted.len();
+                    }
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+/// A sharded LRU cache of decoded log-record values, keyed by
+/// `(file_id, offset)`. Shards are locked independently so concurrent reads
+/// of different keys rarely contend, at the cost of the overall capacity
+/// being split evenly across shards rather than tracked globally.
+pub struct ReadCache {
+    shards: Vec<Mutex<Shard>>,
+    capacity_per_shard: usize,
+    stats: CacheStats,
+}
+
+impl ReadCache {
+    pub fn new(capacity_bytes: usize) -> Self {
+        let shard_count = SHARD_COUNT;
+        ReadCache {
+            shards: (0..shard_count).map(|_| Mutex::new(Shard::new())).collect(),
+            capacity_per_shard: if capacity_bytes == 0 {
+                0
+            } else {
+                (capacity_bytes / shard_count).max(1)
+            },
+            stats: CacheStats::default(),
+        }
+    }
+
+    pub fn get(&self, file_id: u32, offset: u64) -> Option<Bytes> {
+        let key = (file_id, offset);
+        let mut shard = self.shards[self.shard_index(&key)].lock();
+        let found = shard.get(&key);
+        if found.is_some() {
+            self.stats.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.stats.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        found
+    }
+
+    pub fn put(&self, file_id: u32, offset: u64, value: Bytes) {
+        if self.capacity_per_shard == 0 {
+            return;
+        }
+        let key = (file_id, offset);
+        let mut shard = self.shards[self.shard_index(&key)].lock();
+        shard.put(key, value, self.capacity_per_shard);
+    }
+
+    pub fn stats(&self) -> &CacheStats {
+        &self.stats
+    }
+
+    fn shard_index(&self, key: &CacheKey) -> usize {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for b in key.0.to_be_bytes().iter().chain(key.1.to_be_bytes().iter()) {
+            hash ^= *b as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        (hash as usize) % self.shards.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_hit_and_miss() {
+        let cache = ReadCache::new(1024);
+        assert!(cache.get(1, 0).is_none());
+        cache.put(1, 0, Bytes::from_static(b"hello"));
+        assert_eq!(cache.get(1, 0).unwrap(), Bytes::from_static(b"hello"));
+        assert_eq!(cache.stats().hits(), 1);
+        assert_eq!(cache.stats().misses(), 1);
+    }
+
+    #[test]
+    fn test_cache_evicts_when_over_capacity() {
+        let cache = ReadCache::new(SHARD_COUNT * 10);
+        cache.put(1, 0, Bytes::from(vec![0u8; 8]));
+        // Find another key that lands in the same shard as (1, 0).
+        let same_shard = (1u64..)
+            .find(|off| cache.shard_index(&(1, *off)) == cache.shard_index(&(1, 0)))
+            .unwrap();
+        // Same shard: 8 + 8 bytes exceeds the shard's 10-byte budget, so the
+        // least recently used entry, (1, 0), is evicted.
+        cache.put(1, same_shard, Bytes::from(vec![0u8; 8]));
+        assert!(cache.get(1, 0).is_none());
+        assert!(cache.get(1, same_shard).is_some());
+    }
+}
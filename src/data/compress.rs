@@ -0,0 +1,85 @@
+use crate::options::CompressionType;
+use bytes::{Buf, BufMut, BytesMut};
+use prost::{decode_length_delimiter, encode_length_delimiter};
+
+/// Compress a log record's value and wrap it in a small self-describing
+/// envelope: a one-byte codec tag followed by the original (uncompressed)
+/// length and the payload. Storing the tag per-value (rather than globally)
+/// means a single data file can mix compressed and uncompressed records.
+/// [`decode_value`] reads every stored value as such an envelope, so a
+/// value written without one (as in files from before this format) is not
+/// read back unchanged.
+///
+/// When the compressed form is not actually smaller than `value`, the value
+/// is stored uncompressed with a `CompressionType::None` tag instead.
+pub fn encode_value(value: &[u8], codec: CompressionType) -> Vec<u8> {
+    let compressed = match codec {
+        CompressionType::None => None,
+        _ => Some(compress(value, codec)),
+    };
+
+    let (tag, payload) = match compressed {
+        Some(bytes) if bytes.len() < value.len() => (codec, bytes),
+        _ => (CompressionType::None, value.to_vec()),
+    };
+
+    let mut buf = BytesMut::new();
+    buf.put_u8(tag as u8);
+    encode_length_delimiter(value.len(), &mut buf).unwrap();
+    buf.extend_from_slice(&payload);
+    buf.to_vec()
+}
+
+/// Reverse of [`encode_value`]: strip the envelope and decompress the
+/// payload if it was stored compressed.
+pub fn decode_value(buf: &[u8]) -> Vec<u8> {
+    let mut buf = BytesMut::from(buf);
+    let tag = buf.get_u8();
+    let orig_len = decode_length_delimiter(&mut buf).unwrap();
+    let codec = compression_from_u8(tag);
+    match codec {
+        CompressionType::None => buf.to_vec(),
+        _ => decompress(&buf, codec, orig_len),
+    }
+}
+
+fn compress(value: &[u8], codec: CompressionType) -> Vec<u8> {
+    match codec {
+        CompressionType::None => value.to_vec(),
+        CompressionType::Lz4 => lz4_flex::compress_prepend_size(value),
+        CompressionType::Snappy => {
+            let mut encoder = snap::raw::Encoder::new();
+            encoder.compress_vec(value).expect("snappy compress failed")
+        }
+        CompressionType::Zstd => zstd::stream::encode_all(value, 0).expect("zstd compress failed"),
+    }
+}
+
+fn decompress(value: &[u8], codec: CompressionType, orig_len: usize) -> Vec<u8> {
+    match codec {
+        CompressionType::None => value.to_vec(),
+        CompressionType::Lz4 => {
+            lz4_flex::decompress_size_prepended(value).expect("lz4 decompress failed")
+        }
+        CompressionType::Snappy => {
+            let mut decoder = snap::raw::Decoder::new();
+            decoder
+                .decompress_vec(value)
+                .expect("snappy decompress failed")
+        }
+        CompressionType::Zstd => {
+            let mut out = zstd::stream::decode_all(value).expect("zstd decompress failed");
+            out.truncate(orig_len);
+            out
+        }
+    }
+}
+
+fn compression_from_u8(val: u8) -> CompressionType {
+    match val {
+        1 => CompressionType::Lz4,
+        2 => CompressionType::Snappy,
+        3 => CompressionType::Zstd,
+        _ => CompressionType::None,
+    }
+}
@@ -3,11 +3,12 @@ use crate::data::log_record::{
 };
 use crate::errors::{Errors, Result};
 use crate::fio;
-use crate::fio::{new_io_manager, IOManager};
+use crate::fio::fd_cache::FdCache;
+use crate::fio::{new_io_manager, new_io_manager_checked};
 use crate::options::IOType;
 use bytes::{Buf, BytesMut};
 use parking_lot::RwLock;
-use prost::{decode_length_delimiter, length_delimiter_len};
+use prost::decode_length_delimiter;
 use std::ops::Index;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -16,63 +17,192 @@ pub const DATA_FILE_NAME_SUFFIX: &str = ".data";
 pub const HINT_FILE_NAME: &str = "hint-index";
 pub const MERGE_FINISH_FILE_NAME: &str = "merge-finished";
 pub const SEQ_NO_FILE_NAME: &str = "seq-no";
+/// Full dump of the in-memory index, written by `Engine::write_checkpoint`.
+/// Entries are encoded the same way as [`HINT_FILE_NAME`] (key plus an
+/// encoded `LogRecodPos`); written to a `.tmp` path and renamed into place
+/// so a crash mid-write leaves the previous checkpoint (or none) intact.
+pub const CHECKPOINT_FILE_NAME: &str = "index-checkpoint";
+/// Marks a [`CHECKPOINT_FILE_NAME`] as complete and records how far into the
+/// log it accounts for, so `Engine::open` knows both that the checkpoint is
+/// safe to trust and which data-file records it can skip replaying.
+pub const CHECKPOINT_FINISH_FILE_NAME: &str = "index-checkpoint-finished";
+/// Records the current set of data-file ids and merge boundary so
+/// `Engine::open` can skip the full `fs::read_dir` scan `load_data_files`
+/// otherwise needs to discover them. Written to a `.tmp` path and renamed
+/// into place by `Engine::write_manifest`, the same way the checkpoint
+/// files are, so a crash mid-write leaves the previous manifest (or none)
+/// intact rather than a half-written one that could be mistaken for valid.
+pub const MANIFEST_FILE_NAME: &str = "manifest";
+
+/// Where a `DataFile` gets its [`IOManager`] from.
+///
+/// `Direct` holds its own handle open for as long as the `DataFile` lives,
+/// which is what the active (writable) file and the small, fixed set of
+/// bookkeeping files (hint/merge-finished/seq-no) want. `Cached` instead
+/// goes through a shared [`FdCache`] on every access: the cache may have
+/// closed the underlying handle since the last read, in which case this
+/// transparently reopens it.
+enum IoSource {
+    Direct(Arc<dyn fio::IOManager>),
+    Cached {
+        dir_path: PathBuf,
+        io_type: IOType,
+        fd_cache: Arc<FdCache>,
+    },
+}
+
+impl IoSource {
+    fn handle(&self, file_id: u32) -> Result<Arc<dyn fio::IOManager>> {
+        match self {
+            IoSource::Direct(handle) => Ok(handle.clone()),
+            IoSource::Cached {
+                dir_path,
+                io_type,
+                fd_cache,
+            } => {
+                let file_name = get_data_file_name(dir_path.clone(), file_id);
+                let io_type = *io_type;
+                fd_cache.get_or_open(file_id, move || new_io_manager_checked(file_name, io_type))
+            }
+        }
+    }
+}
 
 pub struct DataFile {
     file_id: Arc<RwLock<u32>>,
     write_off: Arc<RwLock<u64>>,
-    io_manager: Box<dyn fio::IOManager>,
+    io_source: IoSource,
 }
 impl DataFile {
     pub fn new(dir_path: PathBuf, file_id: u32, io_type: IOType) -> Result<DataFile> {
         let file_name = get_data_file_name(dir_path, file_id);
-        let io_manager = new_io_manager(file_name, io_type);
+        let io_manager: Arc<dyn fio::IOManager> = Arc::from(new_io_manager(file_name, io_type));
+
+        Ok(DataFile {
+            file_id: Arc::new(RwLock::new(file_id)),
+            write_off: Arc::new(RwLock::new(0)),
+            io_source: IoSource::Direct(io_manager),
+        })
+    }
+
+    /// Open (or reuse) this file's handle through a shared [`FdCache`]
+    /// instead of keeping it open unconditionally.
+    ///
+    /// Used for the older, read-only data files: with tens of thousands of
+    /// them on disk, eagerly opening one `File` per `DataFile` the way
+    /// [`DataFile::new`] does would exhaust `RLIMIT_NOFILE`. Instead, the
+    /// handle is fetched from the cache lazily on every call and may have
+    /// been closed and need reopening if it was least-recently-used out of
+    /// the cache since the previous access.
+    pub fn new_from_cache(
+        dir_path: PathBuf,
+        file_id: u32,
+        io_type: IOType,
+        fd_cache: &Arc<FdCache>,
+    ) -> Result<DataFile> {
+        // Opened once up front so a file that cannot be opened is reported
+        // at load time rather than on the first read.
+        fd_cache.get_or_open(file_id, {
+            let file_name = get_data_file_name(dir_path.clone(), file_id);
+            move || new_io_manager_checked(file_name, io_type)
+        })?;
 
         Ok(DataFile {
             file_id: Arc::new(RwLock::new(file_id)),
             write_off: Arc::new(RwLock::new(0)),
-            io_manager,
+            io_source: IoSource::Cached {
+                dir_path,
+                io_type,
+                fd_cache: fd_cache.clone(),
+            },
         })
     }
 
     pub fn new_hint_file(dir_path: PathBuf) -> Result<DataFile> {
         let file_name = dir_path.join(HINT_FILE_NAME);
-        let io_manager = new_io_manager(file_name, IOType::StandardIO);
+        let io_manager: Arc<dyn fio::IOManager> =
+            Arc::from(new_io_manager(file_name, IOType::StandardIO));
 
         Ok(DataFile {
             file_id: Arc::new(RwLock::new(0)),
             write_off: Arc::new(RwLock::new(0)),
-            io_manager,
+            io_source: IoSource::Direct(io_manager),
         })
     }
 
     pub fn new_merge_fin_file(dir_path: PathBuf) -> Result<DataFile> {
         let file_name = dir_path.join(MERGE_FINISH_FILE_NAME);
-        let io_manager = new_io_manager(file_name, IOType::StandardIO);
+        let io_manager: Arc<dyn fio::IOManager> =
+            Arc::from(new_io_manager(file_name, IOType::StandardIO));
 
         Ok(DataFile {
             file_id: Arc::new(RwLock::new(0)),
             write_off: Arc::new(RwLock::new(0)),
-            io_manager,
+            io_source: IoSource::Direct(io_manager),
         })
     }
 
     pub fn new_seq_no_file(dir_path: PathBuf) -> Result<DataFile> {
         let file_name = dir_path.join(SEQ_NO_FILE_NAME);
-        let io_manager = new_io_manager(file_name, IOType::StandardIO);
+        let io_manager: Arc<dyn fio::IOManager> =
+            Arc::from(new_io_manager(file_name, IOType::StandardIO));
+
+        Ok(DataFile {
+            file_id: Arc::new(RwLock::new(0)),
+            write_off: Arc::new(RwLock::new(0)),
+            io_source: IoSource::Direct(io_manager),
+        })
+    }
+
+    pub fn new_checkpoint_file(dir_path: PathBuf) -> Result<DataFile> {
+        DataFile::new_at_path(dir_path.join(CHECKPOINT_FILE_NAME))
+    }
+
+    pub fn new_checkpoint_tmp_file(dir_path: PathBuf) -> Result<DataFile> {
+        DataFile::new_at_path(dir_path.join(std::format!("{}.tmp", CHECKPOINT_FILE_NAME)))
+    }
+
+    pub fn new_checkpoint_finish_file(dir_path: PathBuf) -> Result<DataFile> {
+        DataFile::new_at_path(dir_path.join(CHECKPOINT_FINISH_FILE_NAME))
+    }
+
+    pub fn new_checkpoint_finish_tmp_file(dir_path: PathBuf) -> Result<DataFile> {
+        DataFile::new_at_path(dir_path.join(std::format!("{}.tmp", CHECKPOINT_FINISH_FILE_NAME)))
+    }
+
+    pub fn new_manifest_file(dir_path: PathBuf) -> Result<DataFile> {
+        DataFile::new_at_path(dir_path.join(MANIFEST_FILE_NAME))
+    }
+
+    pub fn new_manifest_tmp_file(dir_path: PathBuf) -> Result<DataFile> {
+        DataFile::new_at_path(dir_path.join(std::format!("{}.tmp", MANIFEST_FILE_NAME)))
+    }
+
+    fn new_at_path(file_name: PathBuf) -> Result<DataFile> {
+        let io_manager: Arc<dyn fio::IOManager> =
+            Arc::from(new_io_manager(file_name, IOType::StandardIO));
 
         Ok(DataFile {
             file_id: Arc::new(RwLock::new(0)),
             write_off: Arc::new(RwLock::new(0)),
-            io_manager,
+            io_source: IoSource::Direct(io_manager),
         })
     }
+
     pub fn get_write_off(&self) -> u64 {
         let read_guard = self.write_off.read();
         *read_guard
     }
 
+    fn io_manager(&self) -> Result<Arc<dyn fio::IOManager>> {
+        self.io_source.handle(self.get_file_id())
+    }
+
     pub fn file_size(&self) -> u64 {
-        self.io_manager.size()
+        match self.io_manager() {
+            Ok(io_manager) => io_manager.size(),
+            Err(_) => 0,
+        }
     }
 
     pub fn get_file_id(&self) -> u32 {
@@ -81,16 +211,26 @@ impl DataFile {
     }
 
     pub fn sync(&self) -> Result<()> {
-        self.io_manager.sync()
+        self.io_manager()?.sync()
     }
 
     pub fn write(&self, buf: &[u8]) -> Result<usize> {
-        let n_bytes = self.io_manager.write(buf).unwrap();
+        let n_bytes = self.io_manager()?.write(buf).unwrap();
         let mut write_off = self.write_off.write();
         *write_off += n_bytes as u64;
         Ok(n_bytes)
     }
 
+    /// Drop everything past `size` and move the write cursor back to match.
+    ///
+    /// Used during crash recovery to cut away a torn trailing write; only
+    /// ever called on the active file.
+    pub fn truncate(&self, size: u64) -> Result<()> {
+        self.io_manager()?.truncate(size)?;
+        self.set_write_off(size);
+        Ok(())
+    }
+
     pub fn write_hint_record(&self, key: Vec<u8>, pos: LogRecodPos) -> Result<()> {
         let mut hint_record = LogRecord {
             key,
@@ -103,37 +243,36 @@ impl DataFile {
     }
 
     pub fn read_log_record(&self, offset: u64) -> Result<ReadLogRecord> {
+        let io_manager = self.io_manager()?;
         let mut header_buf = BytesMut::zeroed(max_log_record_header_size());
-
-        self.io_manager.read(&mut header_buf, offset)?;
-        let rec_type = header_buf.get_u8();
-        let key_size = decode_length_delimiter(&mut header_buf).unwrap();
-        let value_size = decode_length_delimiter(&mut header_buf).unwrap();
+        let n = io_manager.read(&mut header_buf, offset)?;
+
+        // Sizing the second read requires knowing `key_size`/`value_size`
+        // up front, so the header is parsed here too (on a throwaway copy,
+        // since the original bytes are still needed below); the actual
+        // validation (CRC, record type) happens once, inside
+        // `LogRecord::decode`, on the full header+payload buffer.
+        let mut header_cursor = header_buf.clone();
+        if n == 0 {
+            return Err(Errors::ReadDataFileEOF);
+        }
+        header_cursor.advance(1);
+        let key_size = decode_length_delimiter(&mut header_cursor).unwrap_or(0);
+        let value_size = decode_length_delimiter(&mut header_cursor).unwrap_or(0);
 
         if key_size == 0 && value_size != 0 {
             return Err(Errors::ReadDataFileEOF);
         }
-        let actual_header_size =
-            1 + length_delimiter_len(key_size) + length_delimiter_len(value_size);
+        let actual_header_size = header_buf.len() - header_cursor.len();
+
         let mut kv_buf = BytesMut::zeroed(key_size + value_size + 4);
-        self.io_manager
-            .read(&mut kv_buf, offset + actual_header_size as u64)?;
-        let mut log_record = LogRecord {
-            key: kv_buf.get(..key_size).unwrap().to_vec(),
-            value: kv_buf.get(key_size..kv_buf.len() - 4).unwrap().to_vec(),
+        io_manager.read(&mut kv_buf, offset + actual_header_size as u64)?;
 
-            rec_type: LogRecodType::from_u8(rec_type),
-        };
+        let mut full_buf = BytesMut::with_capacity(actual_header_size + kv_buf.len());
+        full_buf.extend_from_slice(&header_buf[..actual_header_size]);
+        full_buf.extend_from_slice(&kv_buf);
 
-        kv_buf.advance(key_size + value_size);
-        if kv_buf.get_u32() != log_record.get_crc() {
-            return Err(Errors::WrongLogRecordCrc);
-        }
-
-        Ok(ReadLogRecord {
-            record: log_record,
-            size: actual_header_size + key_size + value_size + 4,
-        })
+        LogRecord::decode(&full_buf)
     }
     pub fn set_write_off(&self, offset: u64) {
         let mut write_guard = self.write_off.write();
@@ -141,7 +280,10 @@ impl DataFile {
     }
 
     pub fn set_io_manager(&mut self, dir_path: PathBuf, io_type: IOType) {
-        self.io_manager = new_io_manager(get_data_file_name(dir_path, self.get_file_id()), io_type);
+        self.io_source = IoSource::Direct(Arc::from(new_io_manager(
+            get_data_file_name(dir_path, self.get_file_id()),
+            io_type,
+        )));
     }
 }
 
@@ -1,28 +1,40 @@
-use crate::batch::{log_record_with_seq, parse_log_record_key, NON_TRANSACTION_SEQ_NO};
-use crate::data::data_file::{DataFile, DATA_FILE_NAME_SUFFIX, MERGE_FINISH_FILE_NAME};
+use crate::batch::{log_record_with_seq, parse_log_record_key, PendingCommit, NON_TRANSACTION_SEQ_NO};
+use crate::data::data_file::{
+    get_data_file_name, DataFile, CHECKPOINT_FILE_NAME, CHECKPOINT_FINISH_FILE_NAME,
+    DATA_FILE_NAME_SUFFIX, HINT_FILE_NAME, MANIFEST_FILE_NAME, MERGE_FINISH_FILE_NAME,
+};
 use crate::data::log_record::LogRecodType::DELETED;
-use crate::data::log_record::{LogRecodPos, LogRecodType, LogRecord, TransactionRecord};
+use crate::data::log_record::{
+    decode_log_record_pos, LogRecodPos, LogRecodType, LogRecord, TransactionRecord,
+};
+use crate::cache::ReadCache;
 use crate::errors::{Errors, Result};
+use crate::fio::fd_cache::FdCache;
+use crate::index::bloom::BloomFilter;
 use crate::merge::load_merge_files;
+use crate::snapshot::{Snapshot, SnapshotRegistry};
 use crate::options::IOType::{MemoryMap, StandardIO};
 use crate::options::{IOType, IndexType, Options};
+use crate::util::file::{is_network_filesystem, raise_fd_limit};
 use crate::{index, options};
 use bytes::Bytes;
 use fs2::FileExt;
 use jammdb::Data;
-use log::warn;
+use log::{error, warn};
 use parking_lot::{Mutex, RwLock};
 use std::any::Any;
 use std::cmp::PartialEq;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fs;
 use std::fs::File;
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 
 pub(crate) const FILE_LOCK_NAME: &str = "flock";
 pub const SEQ_NO_KEY: &str = "seq.no";
+const CHECKPOINT_MARKER_KEY: &[u8] = b"checkpoint.marker";
+const MANIFEST_MARKER_KEY: &[u8] = b"manifest.marker";
 
 pub struct Engine {
     pub(crate) option: Arc<Options>,
@@ -30,7 +42,7 @@ pub struct Engine {
     pub(crate) older_file: Arc<RwLock<HashMap<u32, DataFile>>>,
     pub(crate) index: Box<dyn index::Indexer>,
     pub(crate) file_ids: Vec<u32>,
-    pub(crate) batch_commit_lock: Mutex<()>,
+    pub(crate) commit_queue: Mutex<VecDeque<Arc<PendingCommit>>>,
     pub(crate) seq_no: Arc<AtomicUsize>,
     pub(crate) merging_lock: Mutex<()>,
     pub(crate) seq_file_exist: bool,
@@ -38,6 +50,16 @@ pub struct Engine {
     pub(crate) lock_file: File,
     pub(crate) bytes_write: Arc<AtomicUsize>,
     pub(crate) reclaim_size: Arc<AtomicUsize>,
+    /// Dead (overwritten/deleted) byte count per data file, used by
+    /// `merge()` to pick the files actually worth rewriting instead of
+    /// rewriting everything whenever the *global* `reclaim_size`/on-disk
+    /// ratio crosses `data_file_merge_ratio`.
+    pub(crate) file_garbage: Arc<RwLock<HashMap<u32, usize>>>,
+    pub(crate) bloom: RwLock<Arc<BloomFilter>>,
+    pub(crate) read_cache: Arc<ReadCache>,
+    pub(crate) write_seq: Arc<AtomicU64>,
+    pub(crate) snapshots: Arc<SnapshotRegistry>,
+    pub(crate) fd_cache: Arc<FdCache>,
 }
 
 pub struct Stat {
@@ -46,6 +68,25 @@ pub struct Stat {
     pub(crate) reclaim_size: usize,
     disk_size:u64,
 }
+
+impl Stat {
+    pub fn key_num(&self) -> usize {
+        self.key_num
+    }
+
+    pub fn data_file_num(&self) -> usize {
+        self.data_file_num
+    }
+
+    pub fn reclaim_size(&self) -> usize {
+        self.reclaim_size
+    }
+
+    pub fn disk_size(&self) -> u64 {
+        self.disk_size
+    }
+}
+
 const INITIAL_FILE_ID: u32 = 0;
 
 impl Engine {
@@ -54,6 +95,12 @@ impl Engine {
             return Err(e);
         }
 
+        // Tens of thousands of immutable data files can accumulate under
+        // heavy writes/merges; raise the descriptor budget before anything
+        // is opened so the bounded `FdCache` below has more room to work
+        // with before it needs to start evicting.
+        raise_fd_limit();
+
         let mut is_initial = false;
         let options = opts.clone();
         let dir_path = options.dir_path.clone();
@@ -83,7 +130,59 @@ impl Engine {
 
         load_merge_files(dir_path.clone())?;
 
-        let mut data_files = load_data_files(dir_path.clone(), options.mmap_at_startup)?;
+        // mmap over a network share (NFS/CIFS/FUSE) can hand back stale or
+        // torn pages behind the engine's back, so `mmap_at_startup` is
+        // overridden to `StandardIO` there regardless of what the caller
+        // asked for, unless `force_io_type` pins the decision explicitly.
+        let mmap_at_startup = match options.force_io_type {
+            Some(IOType::StandardIO) => false,
+            Some(IOType::MemoryMap) => true,
+            None => options.mmap_at_startup && !is_network_filesystem(&dir_path),
+        };
+        if options.mmap_at_startup && !mmap_at_startup && options.force_io_type.is_none() {
+            warn!(
+                "{} is on a network filesystem; ignoring mmap_at_startup and using standard I/O",
+                dir_path.display()
+            );
+        }
+
+        // jammdb, which backs `IndexType::BPlusTree`, always memory-maps its
+        // own file and has no standard-I/O fallback to force, so
+        // `force_io_type` can't help here; this is just a heads-up that the
+        // same staleness/truncation risk mmap has on a network filesystem
+        // applies to the index file too.
+        if options.index_type == IndexType::BPlusTree && is_network_filesystem(&dir_path) {
+            warn!(
+                "{} is on a network filesystem; the BPlusTree index always memory-maps its file and cannot fall back to standard I/O",
+                dir_path.display()
+            );
+        }
+
+        let fd_cache = Arc::new(FdCache::new(options.fd_cache_capacity));
+        // A manifest (see `Engine::write_manifest`) already lists exactly
+        // which data files exist, so it's used in place of
+        // `load_data_files`'s `fs::read_dir` scan when present and every
+        // file it names is still on disk; otherwise fall back to the scan,
+        // the same as a store that predates the manifest or whose manifest
+        // didn't survive an unclean shutdown.
+        let mut data_files = match load_manifest(&dir_path) {
+            Some((active_file_id, mut older_file_ids)) => {
+                older_file_ids.push(active_file_id);
+                older_file_ids.sort();
+                let io_type = if mmap_at_startup { MemoryMap } else { StandardIO };
+                let mut files = Vec::with_capacity(older_file_ids.len());
+                for file_id in older_file_ids {
+                    files.push(DataFile::new_from_cache(
+                        dir_path.clone(),
+                        file_id,
+                        io_type.clone(),
+                        &fd_cache,
+                    )?);
+                }
+                files
+            }
+            None => load_data_files(dir_path.clone(), mmap_at_startup, &fd_cache)?,
+        };
         let mut file_ids: Vec<u32> = Vec::new();
         for v in data_files.iter() {
             file_ids.push(v.get_file_id());
@@ -100,13 +199,22 @@ impl Engine {
             Some(v) => v,
             None => DataFile::new(dir_path.clone(), INITIAL_FILE_ID, IOType::StandardIO)?,
         };
+        // `MMapIO` cannot truncate, so the active file is switched to
+        // `StandardIO` before `recover_active_file` may need to cut a torn tail.
+        let mut active_file = active_file;
+        active_file.set_io_manager(dir_path.clone(), IOType::StandardIO);
+        // The active file is the only one guaranteed to be written and read
+        // on every operation, so it is pinned and never evicted from the fd
+        // cache even though it was opened directly rather than through it.
+        fd_cache.pin(active_file.get_file_id());
+        recover_active_file(&active_file)?;
         let mut engine = Self {
             option: Arc::new(opts),
             active_file: Arc::new(RwLock::new(active_file)),
             older_file: Arc::new(RwLock::new(older_files)),
             index: index::new_indexer(options.index_type, options.dir_path),
             file_ids,
-            batch_commit_lock: Mutex::new(()),
+            commit_queue: Mutex::new(VecDeque::new()),
             seq_no: Arc::new(AtomicUsize::new(1)),
             merging_lock: Mutex::new(()),
             seq_file_exist: false,
@@ -114,19 +222,43 @@ impl Engine {
             lock_file,
             bytes_write: Arc::new(AtomicUsize::new(0)),
             reclaim_size:Arc::new(AtomicUsize::new(0)),
+            file_garbage: Arc::new(RwLock::new(HashMap::new())),
+            bloom: RwLock::new(Arc::new(BloomFilter::new(
+                options.bloom_filter_expected_keys,
+                options.bloom_filter_fp_rate,
+            ))),
+            read_cache: Arc::new(ReadCache::new(options.cache_capacity_bytes)),
+            write_seq: Arc::new(AtomicU64::new(1)),
+            snapshots: Arc::new(SnapshotRegistry::default()),
+            fd_cache,
         };
 
-        if engine.option.index_type == IndexType::BPlusTree {
-            engine.load_index_from_data_files()?;
-
-            let current_seq_no = engine.load_index_from_data_files()?;
-            if current_seq_no > 0 {
-                engine.seq_no.store(current_seq_no, Ordering::SeqCst);
+        // A valid checkpoint (see `Engine::write_checkpoint`) already has the
+        // index entries as of the position it covers, so only the tail
+        // written since then needs replaying; with no checkpoint (or one
+        // that fails to parse or its CRC check), fall back to replaying
+        // every record.
+        let current_seq_no = match engine.load_checkpoint() {
+            Some((covered_file_id, covered_offset, reclaim_size)) => {
+                engine.reclaim_size.store(reclaim_size, Ordering::SeqCst);
+                engine.load_index_from_data_files(Some((covered_file_id, covered_offset)))?
             }
+            None => engine.load_index_from_data_files(None)?,
+        };
+        if current_seq_no > 0 {
+            engine.seq_no.store(current_seq_no, Ordering::SeqCst);
+        }
 
-            if engine.option.mmap_at_startup {
-                engine.reset_io_type();
-            }
+        // Data files may have been opened with `IOType::MemoryMap` above to
+        // speed up the sequential scan that rebuilds the index; `MMapIO` is
+        // read-only, so every file (the active one included) is switched
+        // back to `StandardIO` before the engine accepts writes. This runs
+        // regardless of index type since `mmap_at_startup` isn't specific
+        // to any one of them. Skipped when `mmap_at_startup` was already
+        // overridden to standard I/O above (e.g. a network filesystem),
+        // since every data file is standard I/O already.
+        if mmap_at_startup {
+            engine.reset_io_type();
         }
 
         if IndexType::BPlusTree == engine.option.index_type {
@@ -138,9 +270,94 @@ impl Engine {
             active_file.set_write_off(active_file.file_size());
         }
 
+        engine.rebuild_bloom_filter();
+
         Ok(engine)
     }
 
+    /// Reopen a store that `Engine::open` has refused because of corruption
+    /// it won't silently paper over.
+    ///
+    /// `Engine::open` already recovers a torn write at the tail of the
+    /// *active* file on its own (that's expected after an unclean
+    /// shutdown), truncating it from its first unreadable record wherever
+    /// that record lies, so reaching for this means the bad record is in an
+    /// older, already rotated file. This scans every data file in write order and truncates the
+    /// *last* file at the start of its first unreadable/torn record, on the
+    /// assumption that bitcask's append-only layout means a partial write
+    /// only ever tears the most recently written file. Corruption found
+    /// anywhere earlier than that is refused rather than silently fixed,
+    /// since losing the tail of an older, supposedly immutable file is not
+    /// a safe default to pick for the caller; see `Errors::CorruptedOlderDataFile`.
+    pub fn repair(opts: Options) -> Result<Engine> {
+        match Engine::open(opts.clone()) {
+            Ok(engine) => Ok(engine),
+            Err(_) => {
+                repair_data_files(&opts.dir_path)?;
+                Engine::open(opts)
+            }
+        }
+    }
+
+    /// Scan every data file the way `merge()` and `repair()` do, reporting
+    /// the `(file_id, offset)` of every record that fails to decode or
+    /// whose CRC mismatches, without mutating anything.
+    ///
+    /// Unlike `repair()`, this never truncates or deletes a file; it's the
+    /// read-only half of the check/repair split, for inspecting a store's
+    /// health (e.g. before deciding whether `repair()` is worth running).
+    /// At most one issue is reported per file: once a file's first bad
+    /// record is hit, nothing past it can be parsed, so the scan moves on
+    /// to the next file.
+    pub fn check(&self) -> Result<Vec<(u32, u64)>> {
+        let mut issues = Vec::new();
+
+        let active_file = self.active_file.read();
+        let older_file = self.older_file.read();
+
+        let mut file_ids: Vec<u32> = older_file.keys().copied().collect();
+        file_ids.push(active_file.get_file_id());
+        file_ids.sort();
+
+        for file_id in file_ids {
+            let mut offset = 0u64;
+            loop {
+                let result = if file_id == active_file.get_file_id() {
+                    active_file.read_log_record(offset)
+                } else {
+                    older_file.get(&file_id).unwrap().read_log_record(offset)
+                };
+                match result {
+                    Ok(result) => offset += result.size as u64,
+                    Err(Errors::ReadDataFileEOF) => break,
+                    Err(_) => {
+                        issues.push((file_id, offset));
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(issues)
+    }
+
+    /// Repopulate the Bloom filter from the current in-memory index.
+    ///
+    /// Called on startup and after merge, since merge rewrites the set of
+    /// live keys into new data files and the filter has no way to remove
+    /// entries for keys that no longer exist.
+    pub(crate) fn rebuild_bloom_filter(&self) {
+        let keys = self.index.list_keys().unwrap_or_default();
+        let bloom = BloomFilter::new(
+            keys.len().max(self.option.bloom_filter_expected_keys),
+            self.option.bloom_filter_fp_rate,
+        );
+        for key in keys.iter() {
+            bloom.insert(key);
+        }
+        *self.bloom.write() = Arc::new(bloom);
+    }
+
     pub fn close(&self) -> Result<()> {
         if !self.option.dir_path.is_dir() {
             return Ok(());
@@ -158,6 +375,10 @@ impl Engine {
 
         let read_guard = self.active_file.read();
         read_guard.sync()?;
+        drop(read_guard);
+
+        self.write_checkpoint()?;
+        self.write_manifest()?;
         self.lock_file.unlock().unwrap();
 
         Ok(())
@@ -168,36 +389,82 @@ impl Engine {
         read_guard.sync()
     }
 
-    pub fn stat(&mut self)->Result<Stat> {
+    pub fn stat(&self)->Result<Stat> {
         let keys=self.list_keys().unwrap();
+        let active_file = self.active_file.read();
         let older_files=self.older_file.read();
+
+        let mut disk_size = active_file.file_size();
+        for file in older_files.values() {
+            disk_size += file.file_size();
+        }
+
         Ok(
             Stat{
                 key_num: keys.len(),
-                data_file_num: keys.len()+1,
+                data_file_num: older_files.len()+1,
                 reclaim_size: self.reclaim_size.load(Ordering::SeqCst),
-                disk_size: 0,
+                disk_size,
             }
         )
     }
+    pub fn cache_stats(&self) -> &crate::cache::CacheStats {
+        self.read_cache.stats()
+    }
+
+    /// Render the current in-memory index as a Graphviz `digraph` for
+    /// debugging: one edge per live key pointing at the data file and
+    /// offset it currently lives at. See [`index::Indexer::export_dot`].
+    pub fn dump_index_dot(&self) -> String {
+        self.index.export_dot()
+    }
+
+    /// Capture a point-in-time view of the database as of the most recent
+    /// completed write.
+    pub fn snapshot(&self) -> Snapshot {
+        // `write_seq` holds the next seq to be handed out; the most recent
+        // completed write has the one before it.
+        let seq = self.write_seq.load(Ordering::SeqCst).saturating_sub(1);
+        Snapshot::new(seq, self.snapshots.clone())
+    }
+
+    /// Read `key` as of `snapshot`.
+    ///
+    /// Only the newest position per key is retained in the index, so this
+    /// can serve a key whose current value was written at or before the
+    /// snapshot; if the key has since been overwritten, the older value is
+    /// no longer available and this returns `Errors::SnapshotValueUnavailable`.
+    pub fn get_at(&self, key: Bytes, snapshot: &Snapshot) -> Result<Bytes> {
+        if key.is_empty() {
+            return Err(Errors::KeyIsEmpty);
+        }
+        let pos = self.index.get(key.to_vec());
+        let pos = match pos {
+            Some(pos) => pos,
+            None => return Err(Errors::KeyIsNotExist),
+        };
+        if pos.seq > snapshot.seq() {
+            return Err(Errors::SnapshotValueUnavailable);
+        }
+        self.get_value_by_position(&pos)
+    }
+
     pub fn put(&self, key: Bytes, value: Bytes) -> Result<()> {
         if key.is_empty() {
             return Err(Errors::KeyIsEmpty);
         }
         let mut record: LogRecord = LogRecord {
             key: log_record_with_seq(key.to_vec(), NON_TRANSACTION_SEQ_NO),
-            value: value.to_vec(),
+            value: crate::data::compress::encode_value(&value, self.option.compression),
             rec_type: LogRecodType::NORMAL,
         };
 
         let log_record_pos = self.append_log_record(&mut record)?;
 
-        if let Some(old_pos)=self.index.put(key.to_vec(),log_record_pos) {
-            self.reclaim_size
-                .fetch_add(old_pos.size as usize, Ordering::SeqCst);
-        }
+        self.update_index(key.to_vec(), LogRecodType::NORMAL, log_record_pos);
+        self.bloom.read().insert(&key);
+
 
-        
         Ok(())
     }
 
@@ -215,15 +482,8 @@ impl Engine {
             value: Default::default(),
             rec_type: LogRecodType::DELETED,
         };
-        self.append_log_record(&mut record)?;
-        //let ok = self.index.delete(key.to_vec());
-        let pos=self.index.get(key.to_vec()).unwrap();
-        self.reclaim_size.fetch_add(pos.size as usize, Ordering::SeqCst);
-        
-        
-        if let Some(old_pos)=self.index.delete(key.to_vec()) {
-            self.reclaim_size.fetch_add(old_pos.size as usize, Ordering::SeqCst);
-        }
+        let log_record_pos = self.append_log_record(&mut record)?;
+        self.update_index(key.to_vec(), LogRecodType::DELETED, log_record_pos);
 
         Ok(())
     }
@@ -232,34 +492,26 @@ impl Engine {
         if key.is_empty() {
             return Err(Errors::KeyIsEmpty);
         }
+        if !self.bloom.read().contains(&key) {
+            return Err(Errors::KeyIsNotExist);
+        }
 
         let pos = self.index.get(key.to_vec());
         if pos.is_none() {
             return Err(Errors::KeyIsNotExist);
         }
         let log_record_pos = pos.unwrap();
-        let active_file = self.active_file.read();
-        let older_file = self.older_file.read();
-        let log_record = match active_file.get_file_id() == log_record_pos.file_id {
-            true => active_file.read_log_record(log_record_pos.offset)?.record,
-            false => {
-                let data_file = older_file.get(&log_record_pos.file_id);
-                if data_file.is_none() {
-                    return Err(Errors::DataFileNotFound);
-                }
-                data_file
-                    .unwrap()
-                    .read_log_record(log_record_pos.offset)?
-                    .record
-            }
-        };
-        if log_record.rec_type == LogRecodType::DELETED {
-            return Err(Errors::KeyIsNotExist);
-        }
-        Ok(log_record.value.into())
+        self.get_value_by_position(&log_record_pos)
     }
 
     pub(crate) fn get_value_by_position(&self, log_record_pos: &LogRecodPos) -> Result<Bytes> {
+        if let Some(cached) = self
+            .read_cache
+            .get(log_record_pos.file_id, log_record_pos.offset)
+        {
+            return Ok(cached);
+        }
+
         let active_file = self.active_file.read();
         let older_file = self.older_file.read();
         let log_record = match active_file.get_file_id() == log_record_pos.file_id {
@@ -278,24 +530,45 @@ impl Engine {
         if log_record.rec_type == LogRecodType::DELETED {
             return Err(Errors::KeyIsNotExist);
         }
-        Ok(log_record.value.into())
+        let value: Bytes = crate::data::compress::decode_value(&log_record.value).into();
+        self.read_cache
+            .put(log_record_pos.file_id, log_record_pos.offset, value.clone());
+        Ok(value)
     }
 
-    pub(crate) fn append_log_record(&self, log_record: &mut LogRecord) -> Result<LogRecodPos> {
+    /// Rotate the active file out to `older_file` and open a fresh one if
+    /// `incoming_len` more bytes would overflow `data_file_size`.
+    ///
+    /// Shared by single-record appends and group commit so both rotate the
+    /// same way.
+    fn rotate_active_file_if_needed(
+        &self,
+        active_file: &mut DataFile,
+        incoming_len: u64,
+    ) -> Result<()> {
+        if active_file.get_write_off() + incoming_len <= self.option.data_file_size {
+            return Ok(());
+        }
         let dir_path = self.option.dir_path.clone();
+        active_file.sync()?;
+        let current_id = active_file.get_file_id();
+        let mut older_file = self.older_file.write();
+        let old_file =
+            DataFile::new_from_cache(dir_path.clone(), current_id, IOType::StandardIO, &self.fd_cache)?;
+        older_file.insert(current_id, old_file);
+        self.fd_cache.unpin(current_id);
+        let new_file = DataFile::new(dir_path.clone(), current_id + 1, IOType::StandardIO)?;
+        self.fd_cache.pin(current_id + 1);
+        *active_file = new_file;
+        Ok(())
+    }
+
+    pub(crate) fn append_log_record(&self, log_record: &mut LogRecord) -> Result<LogRecodPos> {
         let enc_record = log_record.encode();
         let record_len = enc_record.len() as u64;
 
         let mut active_file = self.active_file.write();
-        if active_file.get_write_off() + record_len > self.option.data_file_size {
-            active_file.sync()?;
-            let current_id = active_file.get_file_id();
-            let mut older_file = self.older_file.write();
-            let old_file = DataFile::new(dir_path.clone(), current_id, IOType::StandardIO)?;
-            older_file.insert(current_id, old_file);
-            let new_file = DataFile::new(dir_path.clone(), current_id + 1, IOType::StandardIO)?;
-            *active_file = new_file;
-        }
+        self.rotate_active_file_if_needed(&mut active_file, record_len)?;
         let write_off = active_file.get_write_off();
         active_file.write(&enc_record)?;
 
@@ -317,9 +590,122 @@ impl Engine {
             file_id: active_file.get_file_id(),
             offset: write_off,
             size:enc_record.len() as u32,
+            seq: self.write_seq.fetch_add(1, Ordering::SeqCst),
         })
     }
-    fn load_index_from_data_files(&mut self) -> Result<usize> {
+
+    /// Submit a pre-encoded write-batch buffer to the leader/follower group
+    /// commit and block until it has been written (and, if requested,
+    /// synced).
+    ///
+    /// The caller (`WriteBatch::commit`) has already encoded every record
+    /// in the batch into one contiguous buffer; this just needs to place
+    /// that buffer in the active file and hand back each record's
+    /// resulting `LogRecodPos`. Concurrent callers queue up behind
+    /// `commit_queue`: whichever one finds the queue empty becomes the
+    /// leader, writes every buffer currently queued (including its own and
+    /// any others that arrived meanwhile) with one `DataFile::write` call
+    /// per buffer, issues a single `sync` covering the whole group if any
+    /// of them asked for one, and wakes the followers — turning what would
+    /// otherwise be one `write`+`fsync` pair per transaction into one pair
+    /// per group of concurrently-committing transactions.
+    pub(crate) fn group_commit(&self, job: Arc<PendingCommit>) -> Result<Vec<(Vec<u8>, LogRecodPos)>> {
+        let is_leader = {
+            let mut queue = self.commit_queue.lock();
+            queue.push_back(job.clone());
+            queue.len() == 1
+        };
+
+        if !is_leader {
+            let mut result = job.result.lock();
+            while result.is_none() {
+                job.cv.wait(&mut result);
+            }
+            return result.take().unwrap();
+        }
+
+        loop {
+            let batch: Vec<Arc<PendingCommit>> = {
+                let mut queue = self.commit_queue.lock();
+                queue.drain(..).collect()
+            };
+            if batch.is_empty() {
+                break;
+            }
+
+            let mut active_file = self.active_file.write();
+            let mut want_sync = false;
+            let mut outcomes = Vec::with_capacity(batch.len());
+
+            for pending in batch {
+                let record_len = pending.buf.len() as u64;
+                let write_result = self
+                    .rotate_active_file_if_needed(&mut active_file, record_len)
+                    .and_then(|_| {
+                        let file_id = active_file.get_file_id();
+                        let base_offset = active_file.get_write_off();
+                        active_file.write(&pending.buf)?;
+                        self.bytes_write
+                            .fetch_add(pending.buf.len(), Ordering::SeqCst);
+
+                        let mut offset = base_offset;
+                        let mut positions = Vec::new();
+                        for (key, len) in pending.records.iter() {
+                            let pos = LogRecodPos {
+                                file_id,
+                                offset,
+                                size: *len as u32,
+                                seq: self.write_seq.fetch_add(1, Ordering::SeqCst),
+                            };
+                            if let Some(key) = key {
+                                positions.push((key.clone(), pos));
+                            }
+                            offset += *len as u64;
+                        }
+                        Ok(positions)
+                    });
+
+                want_sync = want_sync || (write_result.is_ok() && pending.want_sync);
+                outcomes.push((pending, write_result));
+            }
+
+            let sync_result = if want_sync {
+                let r = active_file.sync();
+                self.bytes_write.store(0, Ordering::SeqCst);
+                r
+            } else {
+                Ok(())
+            };
+            drop(active_file);
+
+            for (pending, outcome) in outcomes {
+                let outcome = match (&outcome, &sync_result) {
+                    (Ok(_), Err(_)) if pending.want_sync => Err(Errors::FileSyncError),
+                    _ => outcome,
+                };
+                *pending.result.lock() = Some(outcome);
+                pending.cv.notify_all();
+            }
+        }
+
+        // A previous leader may have drained this job and still be writing
+        // it, in which case its result is not set yet.
+        let mut result = job.result.lock();
+        while result.is_none() {
+            job.cv.wait(&mut result);
+        }
+        result.take().unwrap()
+    }
+
+    /// Replay data-file records into the in-memory index.
+    ///
+    /// `covered` is `Some((file_id, offset))` when a checkpoint has already
+    /// loaded the index up to that position (see `Engine::load_checkpoint`):
+    /// files before `file_id` are skipped entirely and the file matching
+    /// `file_id` starts at `offset` instead of the beginning, so only the
+    /// tail written since the checkpoint gets replayed. `None` replays
+    /// every record in every file, as on a store with no checkpoint yet.
+    fn load_index_from_data_files(&mut self, covered: Option<(u32, u64)>) -> Result<usize> {
         let mut current_seq_no: usize = NON_TRANSACTION_SEQ_NO;
 
         if self.file_ids.is_empty() {
@@ -345,7 +731,17 @@ impl Engine {
             if has_merged && *file_id < non_merged_fid {
                 continue;
             }
-            let mut offset = 0;
+            if let Some((covered_file_id, _)) = covered {
+                if *file_id < covered_file_id {
+                    continue;
+                }
+            }
+            let mut offset = match covered {
+                Some((covered_file_id, covered_offset)) if *file_id == covered_file_id => {
+                    covered_offset
+                }
+                _ => 0,
+            };
             loop {
                 let log_record_res = match *file_id == active_file.get_file_id() {
                     true => active_file.read_log_record(offset),
@@ -356,12 +752,29 @@ impl Engine {
                 };
                 let (mut log_record, size) = match log_record_res {
                     Ok(result) => (result.record, result.size),
+                    Err(Errors::ReadDataFileEOF) => break,
+                    Err(Errors::WrongLogRecordCrc) if *file_id == active_file.get_file_id() => {
+                        // The last record of the active file failing its CRC
+                        // check means a crash left a half-written append;
+                        // treat it exactly like EOF rather than refusing to
+                        // open, truncating the active file back to the last
+                        // good boundary so future appends overwrite the torn
+                        // tail.
+                        warn!(
+                            "truncating active data file {} at offset {} after a CRC failure, likely a half-written append",
+                            file_id, offset
+                        );
+                        active_file.truncate(offset)?;
+                        break;
+                    }
                     Err(e) => {
-                        if e == Errors::ReadDataFileEOF {
-                            break;
-                        } else {
-                            return Err(e);
-                        }
+                        // A CRC failure (or any other read error) in a file
+                        // that is no longer being appended to is real
+                        // corruption, not a torn write, so it's a hard
+                        // error. `Engine::repair` only truncates the last
+                        // data file and refuses corruption in any earlier
+                        // one (see `Errors::CorruptedOlderDataFile`).
+                        return Err(e);
                     }
                 };
 
@@ -369,6 +782,10 @@ impl Engine {
                     file_id: *file_id,
                     offset,
                     size: size as u32,
+                    // Records replayed from a previous process have no
+                    // recorded write-seq; 0 sorts before every snapshot
+                    // taken in this process, so they are always visible.
+                    seq: 0,
                 };
 
                 let (real_key, seq_no) = parse_log_record_key(log_record.key.clone());
@@ -410,21 +827,166 @@ impl Engine {
         }
         Ok(current_seq_no)
     }
-    fn update_index(&self, key: Vec<u8>, log_recod_type: LogRecodType, pos: LogRecodPos) {
+    pub(crate) fn update_index(&self, key: Vec<u8>, log_recod_type: LogRecodType, pos: LogRecodPos) {
         if log_recod_type == LogRecodType::NORMAL {
             if let Some(old_pos)=self.index.put(key.clone(), pos){
                 self.reclaim_size.fetch_add(old_pos.size as usize, Ordering::SeqCst);
+                self.add_file_garbage(old_pos.file_id, old_pos.size as usize);
             }
         }
         if log_recod_type == LogRecodType::DELETED {
             let mut size=pos.size;
+            // The tombstone record itself is dead the moment it's written.
+            self.add_file_garbage(pos.file_id, pos.size as usize);
             if let Some(old_pos)=self.index.delete(key) {
                 size+=old_pos.size;
+                self.add_file_garbage(old_pos.file_id, old_pos.size as usize);
             }
             self.reclaim_size.fetch_add(size as usize,Ordering::SeqCst);
         }
     }
 
+    fn add_file_garbage(&self, file_id: u32, size: usize) {
+        *self.file_garbage.write().entry(file_id).or_insert(0) += size;
+    }
+
+    /// Dump the full in-memory index to a checkpoint file so a future
+    /// `Engine::open` can skip replaying everything written before it.
+    ///
+    /// Called on `close` and after every `Engine::merge`. The payload
+    /// (every live key plus its `LogRecodPos`) and the marker that records
+    /// how far into the log it accounts for are each written to a `.tmp`
+    /// path and renamed into place, so a crash mid-write leaves whatever
+    /// checkpoint already existed (or none) intact rather than a
+    /// half-written one `load_checkpoint` might mistake for valid.
+    pub(crate) fn write_checkpoint(&self) -> Result<()> {
+        let active_file = self.active_file.read();
+        let covered_file_id = active_file.get_file_id();
+        let covered_offset = active_file.get_write_off();
+        drop(active_file);
+
+        let dir_path = self.option.dir_path.clone();
+        let tmp_file = DataFile::new_checkpoint_tmp_file(dir_path.clone())?;
+        for key in self.index.list_keys().unwrap_or_default() {
+            if let Some(pos) = self.index.get(key.to_vec()) {
+                tmp_file.write_hint_record(key.to_vec(), pos)?;
+            }
+        }
+        tmp_file.sync()?;
+        fs::rename(
+            dir_path.join(std::format!("{}.tmp", CHECKPOINT_FILE_NAME)),
+            dir_path.join(CHECKPOINT_FILE_NAME),
+        )
+        .map_err(|_| Errors::FailToCreateDatabaseDir)?;
+
+        let reclaim_size = self.reclaim_size.load(Ordering::SeqCst);
+        let mut marker = LogRecord {
+            key: CHECKPOINT_MARKER_KEY.to_vec(),
+            value: std::format!("{}:{}:{}", covered_file_id, covered_offset, reclaim_size)
+                .into_bytes(),
+            rec_type: LogRecodType::NORMAL,
+        };
+        let tmp_fin_file = DataFile::new_checkpoint_finish_tmp_file(dir_path.clone())?;
+        tmp_fin_file.write(&marker.encode())?;
+        tmp_fin_file.sync()?;
+        fs::rename(
+            dir_path.join(std::format!("{}.tmp", CHECKPOINT_FINISH_FILE_NAME)),
+            dir_path.join(CHECKPOINT_FINISH_FILE_NAME),
+        )
+        .map_err(|_| Errors::FailToCreateDatabaseDir)?;
+
+        Ok(())
+    }
+
+    /// Load the index from the on-disk checkpoint, if one exists and is
+    /// intact, returning `Some((covered_file_id, covered_offset,
+    /// reclaim_size))` for `Engine::open` to resume replay from. Returns
+    /// `None` (without touching the index) if there is no checkpoint, or if
+    /// any part of it fails to parse or fails its CRC check — the caller
+    /// falls back to a full replay in that case.
+    fn load_checkpoint(&self) -> Option<(u32, u64, usize)> {
+        let dir_path = self.option.dir_path.clone();
+        if !dir_path.join(CHECKPOINT_FINISH_FILE_NAME).is_file()
+            || !dir_path.join(CHECKPOINT_FILE_NAME).is_file()
+        {
+            return None;
+        }
+
+        let fin_file = DataFile::new_checkpoint_finish_file(dir_path.clone()).ok()?;
+        let marker = fin_file.read_log_record(0).ok()?.record;
+        let value = String::from_utf8(marker.value).ok()?;
+        let mut parts = value.split(':');
+        let covered_file_id: u32 = parts.next()?.parse().ok()?;
+        let covered_offset: u64 = parts.next()?.parse().ok()?;
+        let reclaim_size: usize = parts.next()?.parse().ok()?;
+
+        let checkpoint_file = DataFile::new_checkpoint_file(dir_path).ok()?;
+        let mut entries = Vec::new();
+        let mut offset = 0u64;
+        loop {
+            match checkpoint_file.read_log_record(offset) {
+                Ok(result) => {
+                    entries.push((result.record.key, decode_log_record_pos(result.record.value)));
+                    offset += result.size as u64;
+                }
+                Err(Errors::ReadDataFileEOF) => break,
+                Err(_) => return None,
+            }
+        }
+
+        for (key, mut pos) in entries {
+            // Seqs from a previous process mean nothing to this one's
+            // snapshots; reset them like replayed records.
+            pos.seq = 0;
+            self.index.put(key, pos);
+        }
+        Some((covered_file_id, covered_offset, reclaim_size))
+    }
+
+    /// Dump the current file-id set and merge boundary to a manifest file
+    /// so a future `Engine::open` can skip `load_data_files`'s directory
+    /// scan.
+    ///
+    /// Called on `close` and after every `Engine::merge`, the same as
+    /// `write_checkpoint`: written to a `.tmp` path and renamed into place
+    /// so a crash mid-write leaves the previous manifest (or none) intact.
+    pub(crate) fn write_manifest(&self) -> Result<()> {
+        let dir_path = self.option.dir_path.clone();
+
+        let active_file_id = self.active_file.read().get_file_id();
+        let mut older_file_ids: Vec<u32> = self.older_file.read().keys().copied().collect();
+        older_file_ids.sort();
+
+        let non_merge_fid = read_non_merge_fid(&dir_path)?;
+
+        let value = std::format!(
+            "{}:{}:{}",
+            active_file_id,
+            older_file_ids
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+            non_merge_fid
+        );
+        let mut marker = LogRecord {
+            key: MANIFEST_MARKER_KEY.to_vec(),
+            value: value.into_bytes(),
+            rec_type: LogRecodType::NORMAL,
+        };
+
+        let tmp_file = DataFile::new_manifest_tmp_file(dir_path.clone())?;
+        tmp_file.write(&marker.encode())?;
+        tmp_file.sync()?;
+        fs::rename(
+            dir_path.join(std::format!("{}.tmp", MANIFEST_FILE_NAME)),
+            dir_path.join(MANIFEST_FILE_NAME),
+        )
+        .map_err(|_| Errors::FailToCreateDatabaseDir)?;
+
+        Ok(())
+    }
+
     fn load_seq_no(&self) -> (bool, usize) {
         let file_name = self.option.dir_path.join("SEQ_NO_FILE_NAME");
         if !file_name.is_file() {
@@ -464,7 +1026,104 @@ impl Drop for Engine {
     }
 }
 
-fn load_data_files(dir_path: PathBuf, use_map: bool) -> Result<Vec<DataFile>> {
+/// Scan the active data file for a torn trailing write left by a crash
+/// mid-append and truncate it away.
+///
+/// Every other data file was rotated out of active duty (and synced) before
+/// any later record was appended to it, so only the active file can ever
+/// have been caught mid-write; that's why only it is scanned here. Records
+/// are walked sequentially; the first one that fails to decode marks the
+/// start of a torn trailing write, however large, and everything from
+/// there to the end of the file is truncated away, since a crash can land
+/// mid-append of any value regardless of size.
+fn recover_active_file(active_file: &DataFile) -> Result<()> {
+    let file_size = active_file.file_size();
+    let mut valid_offset = 0u64;
+
+    while valid_offset < file_size {
+        match active_file.read_log_record(valid_offset) {
+            Ok(result) => valid_offset += result.size as u64,
+            Err(Errors::ReadDataFileEOF) => break,
+            Err(_) => {
+                let remaining = file_size - valid_offset;
+                warn!(
+                    "truncating torn trailing write in active data file at offset {}, dropping {} bytes",
+                    valid_offset, remaining
+                );
+                break;
+            }
+        }
+    }
+
+    if valid_offset < file_size {
+        active_file.truncate(valid_offset)?;
+    } else {
+        active_file.set_write_off(valid_offset);
+    }
+    Ok(())
+}
+
+/// The merge boundary `load_index_from_data_files` derives from
+/// `MERGE_FINISH_FILE_NAME`, or `0` if no merge has ever completed. Shared
+/// by `Engine::write_manifest` and `load_manifest` so both agree on what
+/// "stale" means for a cached file-id set.
+fn read_non_merge_fid(dir_path: &PathBuf) -> Result<u32> {
+    let merge_fin_path = dir_path.join(MERGE_FINISH_FILE_NAME);
+    if !merge_fin_path.is_file() {
+        return Ok(0);
+    }
+    let merge_fin_file = DataFile::new_merge_fin_file(dir_path.clone())?;
+    let record = merge_fin_file.read_log_record(0)?.record;
+    let v = String::from_utf8(record.value).map_err(|_| Errors::DataDirectoryCorruped)?;
+    v.parse().map_err(|_| Errors::DataDirectoryCorruped)
+}
+
+/// Read the manifest written by `Engine::write_manifest`, returning
+/// `(active_file_id, older_file_ids)` if it parses and the merge boundary
+/// it recorded still matches `MERGE_FINISH_FILE_NAME` (a mismatch means a
+/// merge ran since the manifest was written and the file-id set it names
+/// is stale) and every file it names is still present on disk.
+///
+/// Returns `None` on a missing, stale, or unparseable manifest; `Engine::open`
+/// falls back to `load_data_files`'s directory scan in that case, the same
+/// as it would for a store that predates the manifest entirely.
+fn load_manifest(dir_path: &PathBuf) -> Option<(u32, Vec<u32>)> {
+    let manifest_path = dir_path.join(MANIFEST_FILE_NAME);
+    if !manifest_path.is_file() {
+        return None;
+    }
+
+    let manifest_file = DataFile::new_manifest_file(dir_path.clone()).ok()?;
+    let record = manifest_file.read_log_record(0).ok()?.record;
+    let value = String::from_utf8(record.value).ok()?;
+    let mut parts = value.split(':');
+    let active_file_id: u32 = parts.next()?.parse().ok()?;
+    let older_file_ids: Vec<u32> = parts
+        .next()?
+        .split(',')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().ok())
+        .collect::<Option<Vec<u32>>>()?;
+    let manifest_non_merge_fid: u32 = parts.next()?.parse().ok()?;
+
+    if manifest_non_merge_fid != read_non_merge_fid(dir_path).ok()? {
+        return None;
+    }
+
+    for file_id in std::iter::once(active_file_id).chain(older_file_ids.iter().copied()) {
+        if !get_data_file_name(dir_path.clone(), file_id).is_file() {
+            return None;
+        }
+    }
+
+    Some((active_file_id, older_file_ids))
+}
+
+fn load_data_files(
+    dir_path: PathBuf,
+    use_map: bool,
+    fd_cache: &Arc<FdCache>,
+) -> Result<Vec<DataFile>> {
     let dir = fs::read_dir(dir_path.clone());
     if dir.is_err() {
         return Err(Errors::FailToReadDatabasedir);
@@ -497,12 +1156,92 @@ fn load_data_files(dir_path: PathBuf, use_map: bool) -> Result<Vec<DataFile>> {
         if use_map {
             io_type = MemoryMap;
         }
-        let data_file = DataFile::new(dir_path.clone(), *file_id, io_type)?;
+        let data_file = DataFile::new_from_cache(dir_path.clone(), *file_id, io_type, fd_cache)?;
         data_files.push(data_file);
     }
 
     Ok(data_files)
 }
+
+/// Find the first data file, in write order, that doesn't read back
+/// cleanly and fix it up: if it's the last file (the one that was active
+/// when the engine crashed), truncate it at the start of the bad record,
+/// since bitcask's append-only layout guarantees a partial write only ever
+/// tears the most recently written file. If an earlier file is the one
+/// that's corrupt, that's unexpected damage to a file that should have
+/// been immutable, so this refuses to silently truncate it and returns
+/// `Errors::CorruptedOlderDataFile` instead.
+///
+/// Used by [`Engine::repair`]; plain standalone `IOManager`s are used here
+/// rather than going through the `FdCache`, since this runs before there is
+/// an `Engine` (or its cache) to speak of.
+fn repair_data_files(dir_path: &PathBuf) -> Result<()> {
+    let dir = fs::read_dir(dir_path).map_err(|_| Errors::FailToReadDatabasedir)?;
+
+    let mut file_ids: Vec<u32> = Vec::new();
+    for file in dir {
+        if let Ok(entry) = file {
+            let file_os_str = entry.file_name();
+            let file_name = file_os_str.to_str().unwrap();
+            if file_name.ends_with(DATA_FILE_NAME_SUFFIX) {
+                let split_names: Vec<&str> = file_name.split(".").collect();
+                if let Ok(file_id) = split_names[0].parse::<u32>() {
+                    file_ids.push(file_id);
+                }
+            }
+        }
+    }
+    file_ids.sort();
+
+    let mut first_bad: Option<(u32, u64)> = None;
+    for file_id in file_ids.iter() {
+        let data_file = DataFile::new(dir_path.clone(), *file_id, IOType::StandardIO)?;
+        let mut offset = 0;
+        loop {
+            match data_file.read_log_record(offset) {
+                Ok(result) => offset += result.size as u64,
+                Err(Errors::ReadDataFileEOF) => break,
+                Err(_) => {
+                    first_bad = Some((*file_id, offset));
+                    break;
+                }
+            }
+        }
+        if first_bad.is_some() {
+            break;
+        }
+    }
+
+    let Some((bad_file_id, bad_offset)) = first_bad else {
+        return Ok(());
+    };
+
+    if bad_file_id != *file_ids.last().unwrap() {
+        error!(
+            "repair: data file {} has corruption before its final record, earlier than the last file written; refusing to discard it",
+            bad_file_id
+        );
+        return Err(Errors::CorruptedOlderDataFile(bad_file_id));
+    }
+
+    warn!(
+        "repair: truncating data file {} at offset {}, discarding a torn trailing write",
+        bad_file_id, bad_offset
+    );
+    let data_file = DataFile::new(dir_path.clone(), bad_file_id, IOType::StandardIO)?;
+    data_file.truncate(bad_offset)?;
+
+    // Any hint-file positions past the truncation point now point at bytes
+    // that no longer exist; invalidate the whole file rather than risk
+    // `load_index_from_hint_files` reloading a stale position.
+    let hint_file = dir_path.join(HINT_FILE_NAME);
+    if hint_file.is_file() {
+        let _ = fs::remove_file(hint_file);
+    }
+
+    Ok(())
+}
+
 fn check_options(opts: &Options) -> Option<Errors> {
     let dir_path = opts.dir_path.to_str();
     if dir_path.is_none() || dir_path.unwrap().len() == 0 {
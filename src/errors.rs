@@ -39,7 +39,29 @@ pub enum Errors {
     ExceedMaxBatchNum,
     #[error("Merging is progressing,please try again later")]
     MergingIsProgressing,
+    #[error("database is already being used by another process")]
+    DataBaseIsUsing,
+    #[error("reclaimable space ratio has not crossed the merge threshold")]
+    CanNotMerge,
+    #[error("not enough disk capacity to run a merge")]
+    NoEnoughDiskCapacity,
+    #[error("data_file_merge_ratio must be between 0 and 1")]
+    DataFileMergeRatioIsInvalid,
     #[error("can not use write batch")]
     CanNotUseWriteBatch,
+    #[error("value for this key was overwritten after the snapshot was taken")]
+    SnapshotValueUnavailable,
+    #[error("async engine task panicked before completing")]
+    AsyncTaskPanicked,
+    #[error("write/sync is not supported on a memory-mapped read-only file")]
+    MmapWriteNotSupported,
+    #[error("fail to truncate file")]
+    FileTruncateError,
+    #[error("unknown log record type byte: {0}")]
+    UnknownLogRecordType(u8),
+    #[error("data file {0} has corruption before its final record; refusing to silently discard an older, already rotated file")]
+    CorruptedOlderDataFile(u32),
+    #[error("can not merge while a snapshot at sequence {0} is still open")]
+    SnapshotOpen(u64),
 }
 pub type Result<T> = result::Result<T, Errors>;
@@ -0,0 +1,98 @@
+use crate::errors::Result;
+use crate::fio::IOManager;
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+
+struct Inner {
+    handles: HashMap<u32, Arc<dyn IOManager>>,
+    order: VecDeque<u32>,
+    pinned: HashSet<u32>,
+}
+
+/// A bounded, LRU cache of open file handles keyed by `file_id`.
+///
+/// With tens of thousands of immutable data files, keeping every handle
+/// open for the life of the engine exhausts `RLIMIT_NOFILE`. This cache
+/// opens handles lazily (via the closure passed to [`FdCache::get_or_open`])
+/// and, once the cache holds more than `capacity` handles, closes the
+/// least-recently-used one by dropping it; the next access transparently
+/// reopens it. A handle can be [`FdCache::pin`]ned so it is never evicted,
+/// which `Engine` uses to keep the single active writable file open.
+pub struct FdCache {
+    capacity: usize,
+    inner: Mutex<Inner>,
+}
+
+impl FdCache {
+    pub fn new(capacity: usize) -> Self {
+        FdCache {
+            capacity: capacity.max(1),
+            inner: Mutex::new(Inner {
+                handles: HashMap::new(),
+                order: VecDeque::new(),
+                pinned: HashSet::new(),
+            }),
+        }
+    }
+
+    /// Return the cached handle for `file_id`, opening it via `open` if it
+    /// is not already cached (either never opened or previously evicted).
+    pub fn get_or_open<F>(&self, file_id: u32, open: F) -> Result<Arc<dyn IOManager>>
+    where
+        F: FnOnce() -> Result<Box<dyn IOManager>>,
+    {
+        {
+            let mut inner = self.inner.lock();
+            if let Some(handle) = inner.handles.get(&file_id).cloned() {
+                inner.touch(file_id);
+                return Ok(handle);
+            }
+        }
+
+        let handle: Arc<dyn IOManager> = Arc::from(open()?);
+        let mut inner = self.inner.lock();
+        inner.insert(file_id, handle.clone(), self.capacity);
+        Ok(handle)
+    }
+
+    /// Exempt `file_id` from eviction, e.g. the currently active write file.
+    pub fn pin(&self, file_id: u32) {
+        self.inner.lock().pinned.insert(file_id);
+    }
+
+    /// Make `file_id` eligible for eviction again (e.g. once it has been
+    /// rotated out of being the active file).
+    pub fn unpin(&self, file_id: u32) {
+        self.inner.lock().pinned.remove(&file_id);
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().handles.len()
+    }
+}
+
+impl Inner {
+    fn touch(&mut self, file_id: u32) {
+        if let Some(pos) = self.order.iter().position(|id| *id == file_id) {
+            self.order.remove(pos);
+        }
+        self.order.push_back(file_id);
+    }
+
+    fn insert(&mut self, file_id: u32, handle: Arc<dyn IOManager>, capacity: usize) {
+        self.handles.insert(file_id, handle);
+        self.touch(file_id);
+
+        while self.handles.len() > capacity {
+            let evict_pos = self.order.iter().position(|id| !self.pinned.contains(id));
+            match evict_pos {
+                Some(pos) => {
+                    let evict = self.order.remove(pos).unwrap();
+                    self.handles.remove(&evict);
+                }
+                None => break,
+            }
+        }
+    }
+}
@@ -73,6 +73,15 @@ impl IOManager for FileIO {
         let metadata = read_guard.metadata().unwrap();
         metadata.len()
     }
+
+    fn truncate(&self, size: u64) -> Result<()> {
+        let write_guard = self.fd.write();
+        if let Err(e) = write_guard.set_len(size) {
+            error!("fail to truncate data file err:{}", e);
+            return Err(Errors::FileTruncateError);
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,91 @@
+use crate::errors::{Errors, Result};
+use crate::fio::IOManager;
+use log::error;
+use memmap2::Mmap;
+use std::fs::OpenOptions;
+use std::path::PathBuf;
+
+/// Read-only `IOManager` backed by a memory-mapped file.
+///
+/// Rebuilding the in-memory index on `Engine::open` reads every log record
+/// in every data file sequentially; doing that through `FileIO::read`
+/// costs one `pread` syscall per record. Mapping the file once up front and
+/// copying out of the mapped slice instead turns those into plain memory
+/// reads, which is why `Engine::open` opens data files with this backend
+/// while loading the index (see `Options::mmap_at_startup`). `write`,
+/// `sync` and `truncate` are not supported here, so the active file is
+/// switched to `IOType::StandardIO` before crash recovery and every other
+/// file is switched back once the index is loaded.
+pub struct MMapIO {
+    // `None` for a zero-length file: `Mmap::map` refuses to map an empty
+    // file, and an empty data file has nothing to read anyway.
+    map: Option<Mmap>,
+}
+
+impl MMapIO {
+    pub fn new(file_name: PathBuf) -> Result<Self> {
+        let file = match OpenOptions::new().read(true).open(&file_name) {
+            Ok(f) => f,
+            Err(e) => {
+                error!("fail to open file for mmap:{}", e);
+                return Err(Errors::FileOpenError);
+            }
+        };
+
+        let len = match file.metadata() {
+            Ok(meta) => meta.len(),
+            Err(e) => {
+                error!("fail to stat file for mmap:{}", e);
+                return Err(Errors::FileOpenError);
+            }
+        };
+        if len == 0 {
+            return Ok(MMapIO { map: None });
+        }
+
+        // Safety: the mapped file is only ever read through this
+        // `IOManager`, which never writes to it; nothing else in the
+        // process truncates a data file out from under a running engine.
+        let map = match unsafe { Mmap::map(&file) } {
+            Ok(map) => map,
+            Err(e) => {
+                error!("fail to mmap file:{}", e);
+                return Err(Errors::FileOpenError);
+            }
+        };
+        Ok(MMapIO { map: Some(map) })
+    }
+}
+
+impl IOManager for MMapIO {
+    fn read(&self, buf: &mut [u8], offset: u64) -> Result<usize> {
+        let map = match &self.map {
+            Some(map) => map,
+            None => return Ok(0),
+        };
+        let offset = offset as usize;
+        if offset >= map.len() {
+            return Ok(0);
+        }
+        let end = (offset + buf.len()).min(map.len());
+        let n = end - offset;
+        buf[..n].copy_from_slice(&map[offset..end]);
+        Ok(n)
+    }
+
+    fn write(&self, _buf: &[u8]) -> Result<usize> {
+        Err(Errors::MmapWriteNotSupported)
+    }
+
+    fn sync(&self) -> Result<()> {
+        Err(Errors::MmapWriteNotSupported)
+    }
+
+    fn size(&self) -> u64 {
+        self.map.as_ref().map(|map| map.len() as u64).unwrap_or(0)
+    }
+
+    fn truncate(&self, _size: u64) -> Result<()> {
+        Err(Errors::MmapWriteNotSupported)
+    }
+}
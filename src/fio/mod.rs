@@ -1,3 +1,4 @@
+pub mod fd_cache;
 mod file_io;
 mod mmap;
 
@@ -14,11 +15,22 @@ pub trait IOManager: Sync + Send {
     fn sync(&self) -> Result<()>;
 
     fn size(&self) -> u64;
+
+    /// Discard everything past `size`, used to drop a torn trailing write
+    /// left by a crash mid-append. Only ever called on the active file.
+    fn truncate(&self, size: u64) -> Result<()>;
 }
 
 pub fn new_io_manager(file_name: PathBuf, io_type: IOType) -> Box<dyn IOManager> {
-    match io_type {
-        IOType::StandardIO => Box::new(FileIO::new(file_name).unwrap()),
-        IOType::MemoryMap => Box::new(MMapIO::new(file_name).unwrap()),
-    }
+    new_io_manager_checked(file_name, io_type).unwrap()
+}
+
+/// Fallible counterpart to [`new_io_manager`], for callers (such as
+/// [`fd_cache::FdCache`]) that open handles lazily and need to surface a
+/// failed reopen instead of panicking.
+pub fn new_io_manager_checked(file_name: PathBuf, io_type: IOType) -> Result<Box<dyn IOManager>> {
+    Ok(match io_type {
+        IOType::StandardIO => Box::new(FileIO::new(file_name)?),
+        IOType::MemoryMap => Box::new(MMapIO::new(file_name)?),
+    })
 }
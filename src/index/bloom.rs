@@ -0,0 +1,91 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A lock-free, fixed-size Bloom filter used to skip index/disk lookups for
+/// keys that are definitely absent.
+///
+/// Bit count `m` and hash count `k` are derived from the expected number of
+/// keys `n` and the desired false-positive rate `p`:
+///   m = ceil(-n * ln(p) / (ln 2)^2)
+///   k = round(m / n * ln 2)
+///
+/// The `k` hashes are produced by double-hashing a single 64-bit FNV-1a
+/// digest split into two 32-bit halves, following Kirsch-Mitzenmacher:
+/// `h_i(key) = (h1 + i * h2) mod m`.
+pub struct BloomFilter {
+    bits: Vec<AtomicU64>,
+    m: usize,
+    k: usize,
+}
+
+impl BloomFilter {
+    pub fn new(expected_keys: usize, false_positive_rate: f64) -> Self {
+        let n = (expected_keys.max(1)) as f64;
+        let p = false_positive_rate.clamp(1e-6, 0.5);
+        let m = (-(n * p.ln()) / std::f64::consts::LN_2.powi(2)).ceil() as usize;
+        let m = m.max(64);
+        let k = ((m as f64 / n) * std::f64::consts::LN_2).round().max(1.0) as usize;
+        let words = (m + 63) / 64;
+        BloomFilter {
+            bits: (0..words).map(|_| AtomicU64::new(0)).collect(),
+            m,
+            k,
+        }
+    }
+
+    pub fn insert(&self, key: &[u8]) {
+        let (h1, h2) = Self::hash_pair(key);
+        for i in 0..self.k {
+            let bit = self.bit_index(h1, h2, i);
+            self.bits[bit / 64].fetch_or(1u64 << (bit % 64), Ordering::Relaxed);
+        }
+    }
+
+    pub fn contains(&self, key: &[u8]) -> bool {
+        let (h1, h2) = Self::hash_pair(key);
+        for i in 0..self.k {
+            let bit = self.bit_index(h1, h2, i);
+            if self.bits[bit / 64].load(Ordering::Relaxed) & (1u64 << (bit % 64)) == 0 {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn bit_index(&self, h1: u32, h2: u32, i: usize) -> usize {
+        let combined = (h1 as u64).wrapping_add((i as u64).wrapping_mul(h2 as u64));
+        (combined % self.m as u64) as usize
+    }
+
+    fn hash_pair(key: &[u8]) -> (u32, u32) {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for b in key {
+            hash ^= *b as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        ((hash & 0xffff_ffff) as u32, (hash >> 32) as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bloom_no_false_negatives() {
+        let bf = BloomFilter::new(1000, 0.01);
+        for i in 0..1000 {
+            bf.insert(format!("key-{}", i).as_bytes());
+        }
+        for i in 0..1000 {
+            assert!(bf.contains(format!("key-{}", i).as_bytes()));
+        }
+    }
+
+    #[test]
+    fn test_bloom_absent_key_usually_reported_absent() {
+        let bf = BloomFilter::new(10, 0.01);
+        bf.insert(b"aaaa");
+        bf.insert(b"bbbb");
+        assert!(!bf.contains(b"nonexistent-key-zzzz"));
+    }
+}
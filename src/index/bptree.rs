@@ -1,4 +1,5 @@
 use crate::data::log_record::{decode_log_record_pos, LogRecodPos};
+use crate::index::merging_iter::MergingIterator;
 use crate::index::{IndexIterator, Indexer};
 use crate::options::IteratorOptions;
 use bytes::Bytes;
@@ -42,6 +43,17 @@ impl IndexIterator for BPTreeIterator {
             return None;
         }
         while let Some(item) = self.items.get(self.current_index) {
+            if let Some(end) = &self.options.end {
+                let past_end = if self.options.reverse {
+                    item.0 <= *end
+                } else {
+                    item.0 >= *end
+                };
+                if past_end {
+                    self.current_index = self.items.len();
+                    return None;
+                }
+            }
             self.current_index += 1;
             let prefix = &self.options.prefix;
             if prefix.is_empty() || item.0.starts_with(prefix) {
@@ -64,7 +76,7 @@ impl BPlusTree {
 }
 
 impl Indexer for BPlusTree {
-    fn put(&self, key: Vec<u8>, pos: LogRecodPos) -> Some(LogRecodPos){
+    fn put(&self, key: Vec<u8>, pos: LogRecodPos) -> Option<LogRecodPos> {
         let mut result=None;
         let tx = self.tree.tx(true).expect("fail to create tx object");
         let bucket = tx.get_bucket(BPTREE_BUCKET_NAME).unwrap();
@@ -110,6 +122,12 @@ impl Indexer for BPlusTree {
         Ok(keys)
     }
 
+    // `jammdb::Bucket::cursor()` only walks forward from the start of the
+    // bucket and has no `seek`/range API, so unlike `BTreeIterator`/
+    // `SkipListIterator` this backend still has to materialize its keys into
+    // a `Vec` up front to support `seek` and reverse iteration. Still routed
+    // through `MergingIterator` (as a single child) so this backend isn't
+    // left out of the lazy-merge machinery the other two now use.
     fn iterator(&self, iterator_options: IteratorOptions) -> Box<dyn IndexIterator> {
         let mut items = Vec::new();
         let tx = self.tree.tx(true).expect("fail to create tx object");
@@ -121,15 +139,17 @@ impl Indexer for BPlusTree {
             items.push((key, pos));
         }
 
-        if iterator_options.reverse {
+        let reverse = iterator_options.reverse;
+        if reverse {
             items.reverse();
         }
 
-        Box::new(BPTreeIterator {
+        let child: Box<dyn IndexIterator> = Box::new(BPTreeIterator {
             items,
             current_index: 0,
             options: iterator_options,
-        })
+        });
+        Box::new(MergingIterator::new(vec![child], reverse))
     }
 }
 
@@ -147,6 +167,8 @@ mod tests {
             LogRecodPos {
                 file_id: 1,
                 offset: 100,
+                size: 10,
+                seq: 0,
             },
         );
         assert!(res1);
@@ -155,6 +177,8 @@ mod tests {
             LogRecodPos {
                 file_id: 2,
                 offset: 101,
+                size: 10,
+                seq: 0,
             },
         );
         assert!(res2);
@@ -164,6 +188,8 @@ mod tests {
             LogRecodPos {
                 file_id: 3,
                 offset: 101,
+                size: 10,
+                seq: 0,
             },
         );
         assert!(res3);
@@ -173,6 +199,8 @@ mod tests {
             LogRecodPos {
                 file_id: 4,
                 offset: 101,
+                size: 10,
+                seq: 0,
             },
         );
         assert!(res4);
@@ -188,6 +216,8 @@ mod tests {
             LogRecodPos {
                 file_id: 1,
                 offset: 100,
+                size: 10,
+                seq: 0,
             },
         );
         assert!(res1);
@@ -196,6 +226,8 @@ mod tests {
             LogRecodPos {
                 file_id: 2,
                 offset: 101,
+                size: 10,
+                seq: 0,
             },
         );
         assert!(res2);
@@ -205,6 +237,8 @@ mod tests {
             LogRecodPos {
                 file_id: 3,
                 offset: 101,
+                size: 10,
+                seq: 0,
             },
         );
         assert!(res3);
@@ -214,6 +248,8 @@ mod tests {
             LogRecodPos {
                 file_id: 4,
                 offset: 101,
+                size: 10,
+                seq: 0,
             },
         );
         assert!(res4);
@@ -236,6 +272,8 @@ mod tests {
             LogRecodPos {
                 file_id: 1,
                 offset: 100,
+                size: 10,
+                seq: 0,
             },
         );
         assert!(res1);
@@ -244,6 +282,8 @@ mod tests {
             LogRecodPos {
                 file_id: 2,
                 offset: 101,
+                size: 10,
+                seq: 0,
             },
         );
         assert!(res2);
@@ -253,6 +293,8 @@ mod tests {
             LogRecodPos {
                 file_id: 3,
                 offset: 101,
+                size: 10,
+                seq: 0,
             },
         );
         assert!(res3);
@@ -262,6 +304,8 @@ mod tests {
             LogRecodPos {
                 file_id: 4,
                 offset: 101,
+                size: 10,
+                seq: 0,
             },
         );
         assert!(res4);
@@ -283,6 +327,8 @@ mod tests {
             LogRecodPos {
                 file_id: 1,
                 offset: 100,
+                size: 10,
+                seq: 0,
             },
         );
         assert!(res1);
@@ -291,6 +337,8 @@ mod tests {
             LogRecodPos {
                 file_id: 2,
                 offset: 101,
+                size: 10,
+                seq: 0,
             },
         );
         assert!(res2);
@@ -300,6 +348,8 @@ mod tests {
             LogRecodPos {
                 file_id: 3,
                 offset: 101,
+                size: 10,
+                seq: 0,
             },
         );
         assert!(res3);
@@ -309,6 +359,8 @@ mod tests {
             LogRecodPos {
                 file_id: 4,
                 offset: 101,
+                size: 10,
+                seq: 0,
             },
         );
         assert!(res4);
@@ -325,6 +377,8 @@ mod tests {
             LogRecodPos {
                 file_id: 1,
                 offset: 100,
+                size: 10,
+                seq: 0,
             },
         );
         assert!(res1);
@@ -333,6 +387,8 @@ mod tests {
             LogRecodPos {
                 file_id: 2,
                 offset: 101,
+                size: 10,
+                seq: 0,
             },
         );
         assert!(res2);
@@ -342,6 +398,8 @@ mod tests {
             LogRecodPos {
                 file_id: 3,
                 offset: 101,
+                size: 10,
+                seq: 0,
             },
         );
         assert!(res3);
@@ -351,6 +409,8 @@ mod tests {
             LogRecodPos {
                 file_id: 4,
                 offset: 101,
+                size: 10,
+                seq: 0,
             },
         );
         assert!(res4);
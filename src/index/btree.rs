@@ -1,9 +1,11 @@
 use crate::data::log_record::LogRecodPos;
+use crate::index::merging_iter::MergingIterator;
 use crate::index::{IndexIterator, Indexer};
 use crate::options::IteratorOptions;
 use bytes::Bytes;
 use parking_lot::RwLock;
 use std::collections::BTreeMap;
+use std::ops::Bound;
 use std::sync::Arc;
 
 pub struct BTree {
@@ -44,59 +46,98 @@ impl Indexer for BTree {
         Ok(keys)
     }
 
-    fn iterator(&self, iterator_options: IteratorOptions) -> Box<dyn IndexIterator> {
+    fn export_dot(&self) -> String {
         let read_guard = self.tree.read();
-        let mut items = Vec::with_capacity(read_guard.len());
-
-        for (k, v) in read_guard.iter() {
-            items.push((k.clone(), v.clone()));
+        let mut dot = String::from("digraph index {\n");
+        for (key, pos) in read_guard.iter() {
+            dot.push_str(&crate::index::format_dot_edge(key, pos));
         }
-        if iterator_options.reverse {
-            items.reverse();
-        }
-        Box::new(BTreeIterator {
-            items,
-            current_index: 0,
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn iterator(&self, iterator_options: IteratorOptions) -> Box<dyn IndexIterator> {
+        let reverse = iterator_options.reverse;
+        let child: Box<dyn IndexIterator> = Box::new(BTreeIterator {
+            tree: self.tree.clone(),
+            cursor: Bound::Unbounded,
+            done: false,
+            current: None,
             options: iterator_options,
-        })
+        });
+        Box::new(MergingIterator::new(vec![child], reverse))
     }
 }
 
+/// Walks the underlying `BTreeMap` lazily via `BTreeMap::range`, re-deriving
+/// the next matching entry on every call instead of collecting the whole
+/// tree into a `Vec` up front. Fed into a single-child [`MergingIterator`]
+/// so the same lazy-merge machinery future multi-file merges will use is
+/// already exercised by the single-backend case.
 pub struct BTreeIterator {
-    items: Vec<(Vec<u8>, LogRecodPos)>,
-    current_index: usize,
+    tree: Arc<RwLock<BTreeMap<Vec<u8>, LogRecodPos>>>,
+    cursor: Bound<Vec<u8>>,
+    done: bool,
+    current: Option<(Vec<u8>, LogRecodPos)>,
     options: IteratorOptions,
 }
 
+impl BTreeIterator {
+    fn advance_raw(&mut self) -> Option<(Vec<u8>, LogRecodPos)> {
+        let read_guard = self.tree.read();
+        let found = if self.options.reverse {
+            read_guard
+                .range((Bound::Unbounded, self.cursor.clone()))
+                .last()
+                .map(|(k, v)| (k.clone(), *v))
+        } else {
+            read_guard
+                .range((self.cursor.clone(), Bound::Unbounded))
+                .next()
+                .map(|(k, v)| (k.clone(), *v))
+        };
+        if let Some((key, _)) = &found {
+            self.cursor = Bound::Excluded(key.clone());
+        }
+        found
+    }
+}
+
 impl IndexIterator for BTreeIterator {
     fn rewind(&mut self) {
-        self.current_index = 0;
+        self.cursor = Bound::Unbounded;
+        self.done = false;
+        self.current = None;
     }
 
     fn seek(&mut self, key: Vec<u8>) {
-        self.current_index = match self.items.binary_search_by(|(x, _)| {
-            if self.options.reverse {
-                x.cmp(&key).reverse()
-            } else {
-                x.cmp(&key)
-            }
-        }) {
-            Ok(index) => index,
-            Err(index) => index,
-        };
+        self.cursor = Bound::Included(key);
+        self.done = false;
+        self.current = None;
     }
 
     fn next(&mut self) -> Option<(&Vec<u8>, &LogRecodPos)> {
-        if self.current_index >= self.items.len() {
+        if self.done {
             return None;
         }
-        while let Some(item) = self.items.get(self.current_index) {
-            self.current_index += 1;
-            let prefix = &self.options.prefix;
-            if prefix.is_empty() || item.0.starts_with(prefix) {
-                return Some((&item.0, &item.1));
+        while let Some((key, pos)) = self.advance_raw() {
+            if let Some(end) = &self.options.end {
+                let past_end = if self.options.reverse {
+                    key <= *end
+                } else {
+                    key >= *end
+                };
+                if past_end {
+                    self.done = true;
+                    return None;
+                }
+            }
+            if self.options.prefix.is_empty() || key.starts_with(&self.options.prefix) {
+                self.current = Some((key, pos));
+                return self.current.as_ref().map(|(k, p)| (k, p));
             }
         }
+        self.done = true;
         None
     }
 }
@@ -112,6 +153,8 @@ mod tests {
             LogRecodPos {
                 file_id: 1,
                 offset: 10,
+                size: 10,
+                seq: 0,
             },
         );
         assert_eq!(res.is_none(), true);
@@ -120,6 +163,8 @@ mod tests {
             LogRecodPos {
                 file_id: 2,
                 offset: 20,
+                size: 10,
+                seq: 0,
             },
         );
         assert_eq!(re1.is_none(), true);
@@ -132,6 +177,8 @@ mod tests {
             LogRecodPos {
                 file_id: 1,
                 offset: 10,
+                size: 10,
+                seq: 0,
             },
         );
         assert_eq!(res.is_some(), true);
@@ -140,6 +187,8 @@ mod tests {
             LogRecodPos {
                 file_id: 2,
                 offset: 20,
+                size: 10,
+                seq: 0,
             },
         );
         assert_eq!(re1.is_some(), true);
@@ -163,6 +212,8 @@ mod tests {
             LogRecodPos {
                 file_id: 1,
                 offset: 10,
+                size: 10,
+                seq: 0,
             },
         );
         bt.put(
@@ -170,6 +221,8 @@ mod tests {
             LogRecodPos {
                 file_id: 2,
                 offset: 20,
+                size: 10,
+                seq: 0,
             },
         );
         let res1 = bt.delete("".as_bytes().to_vec());
@@ -191,6 +244,8 @@ mod tests {
             LogRecodPos {
                 file_id: 1,
                 offset: 10,
+                size: 10,
+                seq: 0,
             },
         );
         let mut iter2 = bt.iterator(IteratorOptions::default());
@@ -208,6 +263,8 @@ mod tests {
             LogRecodPos {
                 file_id: 1,
                 offset: 10,
+                size: 10,
+                seq: 0,
             },
         );
         bt.put(
@@ -215,6 +272,8 @@ mod tests {
             LogRecodPos {
                 file_id: 1,
                 offset: 10,
+                size: 10,
+                seq: 0,
             },
         );
         bt.put(
@@ -222,6 +281,8 @@ mod tests {
             LogRecodPos {
                 file_id: 1,
                 offset: 10,
+                size: 10,
+                seq: 0,
             },
         );
         let mut iter3 = bt.iterator(IteratorOptions::default());
@@ -253,6 +314,8 @@ mod tests {
             LogRecodPos {
                 file_id: 1,
                 offset: 10,
+                size: 10,
+                seq: 0,
             },
         );
         bt.put(
@@ -260,6 +323,8 @@ mod tests {
             LogRecodPos {
                 file_id: 1,
                 offset: 10,
+                size: 10,
+                seq: 0,
             },
         );
         bt.put(
@@ -267,6 +332,8 @@ mod tests {
             LogRecodPos {
                 file_id: 1,
                 offset: 10,
+                size: 10,
+                seq: 0,
             },
         );
         println!("{:?}", iter1.next());
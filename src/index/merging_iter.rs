@@ -0,0 +1,116 @@
+use crate::data::log_record::LogRecodPos;
+use crate::index::IndexIterator;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// The current head of one child iterator, ordered by key so the merge can
+/// be driven by a binary heap instead of a `Vec` holding every key.
+struct Head {
+    key: Vec<u8>,
+    pos: LogRecodPos,
+    child: usize,
+    reverse: bool,
+}
+
+impl PartialEq for Head {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+impl Eq for Head {}
+
+impl PartialOrd for Head {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Head {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // `BinaryHeap` is a max-heap, so for forward iteration (smallest key
+        // next) the key order is inverted; for reverse iteration the
+        // natural order already pops the largest key first.
+        let ord = self.key.cmp(&other.key);
+        if self.reverse {
+            ord
+        } else {
+            ord.reverse()
+        }
+    }
+}
+
+/// A lazy k-way merge over `N` ordered child iterators.
+///
+/// Rather than collecting every child into one `Vec` and sorting it
+/// (O(total keys) memory), this keeps only the current head of each child on
+/// a binary heap (O(N) memory) and repeatedly pops the smallest (or, in
+/// reverse mode, largest) head, advancing only that one child. This is the
+/// same shape as merging several sorted runs during an external sort, and
+/// is the building block future multi-file merges can reuse: each data
+/// file's live keys become one child iterator here instead of requiring a
+/// bespoke merge routine.
+pub struct MergingIterator {
+    children: Vec<Box<dyn IndexIterator>>,
+    heap: BinaryHeap<Head>,
+    current: Option<Head>,
+    reverse: bool,
+}
+
+impl MergingIterator {
+    pub fn new(children: Vec<Box<dyn IndexIterator>>, reverse: bool) -> Self {
+        let mut iter = MergingIterator {
+            children,
+            heap: BinaryHeap::new(),
+            current: None,
+            reverse,
+        };
+        iter.refill();
+        iter
+    }
+
+    fn refill(&mut self) {
+        self.heap.clear();
+        for (idx, child) in self.children.iter_mut().enumerate() {
+            if let Some((key, pos)) = child.next() {
+                self.heap.push(Head {
+                    key: key.clone(),
+                    pos: *pos,
+                    child: idx,
+                    reverse: self.reverse,
+                });
+            }
+        }
+    }
+}
+
+impl IndexIterator for MergingIterator {
+    fn rewind(&mut self) {
+        for child in self.children.iter_mut() {
+            child.rewind();
+        }
+        self.current = None;
+        self.refill();
+    }
+
+    fn seek(&mut self, key: Vec<u8>) {
+        for child in self.children.iter_mut() {
+            child.seek(key.clone());
+        }
+        self.current = None;
+        self.refill();
+    }
+
+    fn next(&mut self) -> Option<(&Vec<u8>, &LogRecodPos)> {
+        let winner = self.heap.pop()?;
+        if let Some((key, pos)) = self.children[winner.child].next() {
+            self.heap.push(Head {
+                key: key.clone(),
+                pos: *pos,
+                child: winner.child,
+                reverse: self.reverse,
+            });
+        }
+        self.current = Some(winner);
+        self.current.as_ref().map(|h| (&h.key, &h.pos))
+    }
+}
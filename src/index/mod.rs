@@ -1,5 +1,7 @@
+pub mod bloom;
 mod bptree;
 mod btree;
+pub mod merging_iter;
 mod skiplist;
 
 use crate::data::log_record::LogRecodPos;
@@ -10,7 +12,7 @@ use crate::options::{IndexType, IteratorOptions, Options};
 use bytes::Bytes;
 use std::path::PathBuf;
 
-pub trait Indexer {
+pub trait Indexer: Send + Sync {
     fn put(&self, key: Vec<u8>, pos: LogRecodPos) -> Option<LogRecodPos>;
     fn get(&self, key: Vec<u8>) -> Option<LogRecodPos>;
     fn delete(&self, key: Vec<u8>) -> Option<LogRecodPos>;
@@ -18,6 +20,34 @@ pub trait Indexer {
     fn list_keys(&self) -> Result<Vec<Bytes>>;
 
     fn iterator(&self, iterator_options: IteratorOptions) -> Box<dyn IndexIterator>;
+
+    /// Render the index as a Graphviz `digraph`, one edge per live key
+    /// pointing at the data file and offset it currently lives at, so
+    /// operators can eyeball key distribution and spot hot files before a
+    /// merge. The default walks `list_keys`/`get`; `BTree` overrides this
+    /// with a single pass over its tree instead.
+    fn export_dot(&self) -> String {
+        let mut dot = String::from("digraph index {\n");
+        if let Ok(keys) = self.list_keys() {
+            for key in keys.iter() {
+                if let Some(pos) = self.get(key.to_vec()) {
+                    dot.push_str(&format_dot_edge(key, &pos));
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Shared by every `export_dot` implementation so the edge format (and its
+/// quote-escaping) stays consistent across index backends.
+pub(crate) fn format_dot_edge(key: &[u8], pos: &LogRecodPos) -> String {
+    let key_label = String::from_utf8_lossy(key).replace('\\', "\\\\").replace('"', "\\\"");
+    std::format!(
+        "    \"{}\" -> \"file_id={}, offset={}\";\n",
+        key_label, pos.file_id, pos.offset
+    )
 }
 
 pub fn new_indexer(index_type: IndexType, dir_path: PathBuf) -> Box<dyn Indexer> {
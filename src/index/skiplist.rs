@@ -1,10 +1,10 @@
 use crate::data::log_record::LogRecodPos;
-use crate::index::btree::BTreeIterator;
+use crate::index::merging_iter::MergingIterator;
 use crate::index::{IndexIterator, Indexer};
 use crate::options::IteratorOptions;
 use bytes::Bytes;
 use crossbeam_skiplist::SkipMap;
-use std::ops::Index;
+use std::ops::Bound;
 use std::sync::Arc;
 
 pub struct SkipList {
@@ -19,49 +19,83 @@ impl SkipList {
     }
 }
 
+/// Walks the underlying `SkipMap` lazily via `SkipMap::range`, re-deriving
+/// the next matching entry on every call instead of collecting the whole
+/// list into a `Vec` up front. Fed into a single-child [`MergingIterator`]
+/// so the same lazy-merge machinery future multi-file merges will use is
+/// already exercised by the single-backend case.
 pub struct SkipListIterator {
-    items: Vec<(Vec<u8>, LogRecodPos)>,
-    current_index: usize,
+    skl: Arc<SkipMap<Vec<u8>, LogRecodPos>>,
+    cursor: Bound<Vec<u8>>,
+    done: bool,
+    current: Option<(Vec<u8>, LogRecodPos)>,
     options: IteratorOptions,
 }
 
+impl SkipListIterator {
+    fn advance_raw(&mut self) -> Option<(Vec<u8>, LogRecodPos)> {
+        let found = if self.options.reverse {
+            self.skl
+                .range((Bound::Unbounded, self.cursor.clone()))
+                .last()
+                .map(|e| (e.key().clone(), *e.value()))
+        } else {
+            self.skl
+                .range((self.cursor.clone(), Bound::Unbounded))
+                .next()
+                .map(|e| (e.key().clone(), *e.value()))
+        };
+        if let Some((key, _)) = &found {
+            self.cursor = Bound::Excluded(key.clone());
+        }
+        found
+    }
+}
+
 impl IndexIterator for SkipListIterator {
     fn rewind(&mut self) {
-        self.current_index = 0;
+        self.cursor = Bound::Unbounded;
+        self.done = false;
+        self.current = None;
     }
 
     fn seek(&mut self, key: Vec<u8>) {
-        self.current_index = match self.items.binary_search_by(|(x, _)| {
-            if self.options.reverse {
-                x.cmp(&key).reverse()
-            } else {
-                x.cmp(&key)
-            }
-        }) {
-            Ok(index) => index,
-            Err(index) => index,
-        };
+        self.cursor = Bound::Included(key);
+        self.done = false;
+        self.current = None;
     }
 
     fn next(&mut self) -> Option<(&Vec<u8>, &LogRecodPos)> {
-        if self.current_index >= self.items.len() {
+        if self.done {
             return None;
         }
-        while let Some(item) = self.items.get(self.current_index) {
-            self.current_index += 1;
-            let prefix = &self.options.prefix;
-            if prefix.is_empty() || item.0.starts_with(prefix) {
-                return Some((&item.0, &item.1));
+        while let Some((key, pos)) = self.advance_raw() {
+            if let Some(end) = &self.options.end {
+                let past_end = if self.options.reverse {
+                    key <= *end
+                } else {
+                    key >= *end
+                };
+                if past_end {
+                    self.done = true;
+                    return None;
+                }
+            }
+            if self.options.prefix.is_empty() || key.starts_with(&self.options.prefix) {
+                self.current = Some((key, pos));
+                return self.current.as_ref().map(|(k, p)| (k, p));
             }
         }
+        self.done = true;
         None
     }
 }
 
 impl Indexer for SkipList {
-    fn put(&self, key: Vec<u8>, pos: LogRecodPos) -> bool {
+    fn put(&self, key: Vec<u8>, pos: LogRecodPos) -> Option<LogRecodPos> {
+        let old = self.skl.get(&key).map(|entry| *entry.value());
         self.skl.insert(key, pos);
-        true
+        old
     }
 
     fn get(&self, key: Vec<u8>) -> Option<LogRecodPos> {
@@ -71,9 +105,8 @@ impl Indexer for SkipList {
         None
     }
 
-    fn delete(&self, key: Vec<u8>) -> bool {
-        let remove_res = self.skl.remove(&key);
-        remove_res.is_some()
+    fn delete(&self, key: Vec<u8>) -> Option<LogRecodPos> {
+        self.skl.remove(&key).map(|entry| *entry.value())
     }
 
     fn list_keys(&self) -> crate::errors::Result<Vec<Bytes>> {
@@ -85,19 +118,15 @@ impl Indexer for SkipList {
     }
 
     fn iterator(&self, iterator_options: IteratorOptions) -> Box<dyn IndexIterator> {
-        let mut items = Vec::with_capacity(self.skl.len());
-
-        for e in self.skl.iter() {
-            items.push((e.key().clone(), *e.value()));
-        }
-        if iterator_options.reverse {
-            items.reverse();
-        }
-        Box::new(SkipListIterator {
-            items,
-            current_index: 0,
+        let reverse = iterator_options.reverse;
+        let child: Box<dyn IndexIterator> = Box::new(SkipListIterator {
+            skl: self.skl.clone(),
+            cursor: Bound::Unbounded,
+            done: false,
+            current: None,
             options: iterator_options,
-        })
+        });
+        Box::new(MergingIterator::new(vec![child], reverse))
     }
 }
 
@@ -113,33 +142,41 @@ mod tests {
             LogRecodPos {
                 file_id: 1123,
                 offset: 1232,
+                size: 10,
+                seq: 0,
             },
         );
-        assert!(res1);
+        assert!(res1.is_none());
         let res2 = skl.put(
             "bbcd".as_bytes().to_vec(),
             LogRecodPos {
                 file_id: 1123,
                 offset: 1232,
+                size: 10,
+                seq: 0,
             },
         );
-        assert!(res2);
+        assert!(res2.is_none());
         let res3 = skl.put(
             "cccd".as_bytes().to_vec(),
             LogRecodPos {
                 file_id: 1123,
                 offset: 1232,
+                size: 10,
+                seq: 0,
             },
         );
-        assert!(res3);
+        assert!(res3.is_none());
         let res4 = skl.put(
             "cced".as_bytes().to_vec(),
             LogRecodPos {
                 file_id: 1124,
                 offset: 1112,
+                size: 10,
+                seq: 0,
             },
         );
-        assert!(res4);
+        assert!(res4.is_none());
     }
 
     #[test]
@@ -150,33 +187,41 @@ mod tests {
             LogRecodPos {
                 file_id: 1123,
                 offset: 1232,
+                size: 10,
+                seq: 0,
             },
         );
-        assert!(res1);
+        assert!(res1.is_none());
         let res2 = skl.put(
             "bbcd".as_bytes().to_vec(),
             LogRecodPos {
                 file_id: 1123,
                 offset: 1232,
+                size: 10,
+                seq: 0,
             },
         );
-        assert!(res2);
+        assert!(res2.is_none());
         let res3 = skl.put(
             "cccd".as_bytes().to_vec(),
             LogRecodPos {
                 file_id: 1123,
                 offset: 1232,
+                size: 10,
+                seq: 0,
             },
         );
-        assert!(res3);
+        assert!(res3.is_none());
         let res4 = skl.put(
             "cced".as_bytes().to_vec(),
             LogRecodPos {
                 file_id: 1124,
                 offset: 1112,
+                size: 10,
+                seq: 0,
             },
         );
-        assert!(res4);
+        assert!(res4.is_none());
 
         let get_res1 = skl.get("aacd".as_bytes().to_vec());
         assert!(get_res1.is_some());
@@ -199,38 +244,46 @@ mod tests {
             LogRecodPos {
                 file_id: 1123,
                 offset: 1232,
+                size: 10,
+                seq: 0,
             },
         );
-        assert!(res1);
+        assert!(res1.is_none());
         let res2 = skl.put(
             "bbcd".as_bytes().to_vec(),
             LogRecodPos {
                 file_id: 1123,
                 offset: 1232,
+                size: 10,
+                seq: 0,
             },
         );
-        assert!(res2);
+        assert!(res2.is_none());
         let res3 = skl.put(
             "cccd".as_bytes().to_vec(),
             LogRecodPos {
                 file_id: 1123,
                 offset: 1232,
+                size: 10,
+                seq: 0,
             },
         );
-        assert!(res3);
+        assert!(res3.is_none());
         let res4 = skl.put(
             "cced".as_bytes().to_vec(),
             LogRecodPos {
                 file_id: 1124,
                 offset: 1112,
+                size: 10,
+                seq: 0,
             },
         );
-        assert!(res4);
+        assert!(res4.is_none());
 
         let delete_res1 = skl.delete("aacd".as_bytes().to_vec());
-        assert!(delete_res1);
+        assert!(delete_res1.is_some());
         let delete_res2 = skl.delete("bbcd".as_bytes().to_vec());
-        assert!(delete_res2);
+        assert!(delete_res2.is_some());
         println!("{:#?}", skl.list_keys());
     }
     #[test]
@@ -241,33 +294,41 @@ mod tests {
             LogRecodPos {
                 file_id: 1123,
                 offset: 1232,
+                size: 10,
+                seq: 0,
             },
         );
-        assert!(res1);
+        assert!(res1.is_none());
         let res2 = skl.put(
             "bbcd".as_bytes().to_vec(),
             LogRecodPos {
                 file_id: 1123,
                 offset: 1232,
+                size: 10,
+                seq: 0,
             },
         );
-        assert!(res2);
+        assert!(res2.is_none());
         let res3 = skl.put(
             "cccd".as_bytes().to_vec(),
             LogRecodPos {
                 file_id: 1123,
                 offset: 1232,
+                size: 10,
+                seq: 0,
             },
         );
-        assert!(res3);
+        assert!(res3.is_none());
         let res4 = skl.put(
             "cced".as_bytes().to_vec(),
             LogRecodPos {
                 file_id: 1124,
                 offset: 1112,
+                size: 10,
+                seq: 0,
             },
         );
-        assert!(res4);
+        assert!(res4.is_none());
         println!("{:#?}", skl.list_keys());
     }
     #[test]
@@ -278,33 +339,41 @@ mod tests {
             LogRecodPos {
                 file_id: 1123,
                 offset: 1232,
+                size: 10,
+                seq: 0,
             },
         );
-        assert!(res1);
+        assert!(res1.is_none());
         let res2 = skl.put(
             "bbcd".as_bytes().to_vec(),
             LogRecodPos {
                 file_id: 1123,
                 offset: 1232,
+                size: 10,
+                seq: 0,
             },
         );
-        assert!(res2);
+        assert!(res2.is_none());
         let res3 = skl.put(
             "cccd".as_bytes().to_vec(),
             LogRecodPos {
                 file_id: 1123,
                 offset: 1232,
+                size: 10,
+                seq: 0,
             },
         );
-        assert!(res3);
+        assert!(res3.is_none());
         let res4 = skl.put(
             "cced".as_bytes().to_vec(),
             LogRecodPos {
                 file_id: 1124,
                 offset: 1112,
+                size: 10,
+                seq: 0,
             },
         );
-        assert!(res4);
+        assert!(res4.is_none());
 
         let mut opts = IteratorOptions::default();
         opts.reverse = true;
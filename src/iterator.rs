@@ -2,6 +2,7 @@ use crate::db::Engine;
 use crate::errors::Result;
 use crate::index::IndexIterator;
 use crate::options::IteratorOptions;
+use crate::snapshot::Snapshot;
 use bytes::{Bytes, BytesMut};
 use parking_lot::RwLock;
 use std::sync::Arc;
@@ -9,6 +10,9 @@ use std::sync::Arc;
 pub struct Iterator<'a> {
     index_iter: Arc<RwLock<Box<dyn IndexIterator>>>,
     engine: &'a Engine,
+    /// When set, entries written after this sequence number are skipped so
+    /// the iterator presents a consistent "as of" view.
+    snapshot_seq: Option<u64>,
 }
 
 impl Engine {
@@ -16,6 +20,18 @@ impl Engine {
         Iterator {
             index_iter: Arc::new(RwLock::new(self.index.iterator(options))),
             engine: self,
+            snapshot_seq: None,
+        }
+    }
+
+    /// Like [`Engine::iter`], but restricted to the view captured by
+    /// `snapshot`: entries whose `LogRecodPos::seq` is newer than the
+    /// snapshot are skipped.
+    pub fn iter_at(&self, options: IteratorOptions, snapshot: &Snapshot) -> Iterator {
+        Iterator {
+            index_iter: Arc::new(RwLock::new(self.index.iterator(options))),
+            engine: self,
+            snapshot_seq: Some(snapshot.seq()),
         }
     }
 
@@ -23,6 +39,36 @@ impl Engine {
         self.index.list_keys()
     }
 
+    /// Bounded range scan: seeks to `start` and returns up to `limit`
+    /// key/value pairs going forward, or backward when `reverse` is set,
+    /// stopping before `end` if given. Lets a caller page through the
+    /// keyspace (pass the last returned key back in as the next call's
+    /// `start`; `start` is inclusive, so that key comes back again as the
+    /// first item of the next page) or walk a descending range without pulling the whole
+    /// keyspace through [`Engine::list_keys`].
+    pub fn scan(
+        &self,
+        start: Vec<u8>,
+        end: Option<Vec<u8>>,
+        reverse: bool,
+        limit: usize,
+    ) -> Result<Vec<(Bytes, Bytes)>> {
+        let mut iter_opts = IteratorOptions::default();
+        iter_opts.reverse = reverse;
+        iter_opts.end = end;
+        let mut iter = self.iter(iter_opts);
+        iter.seek(start);
+
+        let mut results = Vec::new();
+        while results.len() < limit {
+            match iter.try_next()? {
+                Some(item) => results.push(item),
+                None => break,
+            }
+        }
+        Ok(results)
+    }
+
     pub fn fold<F>(&self, f: F) -> Result<()>
     where
         Self: Sized,
@@ -38,6 +84,53 @@ impl Engine {
     }
 }
 
+/// Like [`Iterator`], but owns an `Arc<Engine>` instead of borrowing
+/// `&Engine`, so it can outlive the call that created it — e.g. to drive a
+/// `'static` stream such as a chunked HTTP response, where `Iterator`'s
+/// borrowed lifetime can't be satisfied.
+pub struct OwnedIterator {
+    index_iter: Arc<RwLock<Box<dyn IndexIterator>>>,
+    engine: Arc<Engine>,
+    snapshot_seq: Option<u64>,
+}
+
+impl Engine {
+    pub fn iter_owned(self: Arc<Self>, options: IteratorOptions) -> OwnedIterator {
+        OwnedIterator {
+            index_iter: Arc::new(RwLock::new(self.index.iterator(options))),
+            engine: self,
+            snapshot_seq: None,
+        }
+    }
+}
+
+impl OwnedIterator {
+    pub fn rewind(&mut self) {
+        self.index_iter.write().rewind();
+    }
+
+    pub fn seek(&mut self, key: Vec<u8>) {
+        self.index_iter.write().seek(key);
+    }
+
+    /// Like [`Iterator::try_next`]: surfaces a bad value position (a
+    /// truncated data file, a bad CRC, a dangling position after a crash)
+    /// as an `errors::Result` instead of panicking.
+    pub fn try_next(&mut self) -> Result<Option<(Bytes, Bytes)>> {
+        let mut index_iter = self.index_iter.write();
+        while let Some(item) = index_iter.next() {
+            if let Some(snapshot_seq) = self.snapshot_seq {
+                if item.1.seq > snapshot_seq {
+                    continue;
+                }
+            }
+            let value = self.engine.get_value_by_position(item.1)?;
+            return Ok(Some((Bytes::from(item.0.to_vec()), value)));
+        }
+        Ok(None)
+    }
+}
+
 impl Iterator<'_> {
     pub fn rewind(&mut self) {
         let mut index_iter = self.index_iter.write();
@@ -49,16 +142,45 @@ impl Iterator<'_> {
         index_iter.seek(key);
     }
 
+    /// Thin wrapper over [`Iterator::try_next`] kept for source
+    /// compatibility with existing callers; panics on the same errors
+    /// `try_next` surfaces (a truncated data file, a bad CRC, a dangling
+    /// position after a crash), so prefer `try_next` or the
+    /// `std::iter::Iterator` impl on stores that may be partially
+    /// corrupted.
     pub fn next(&mut self) -> Option<(Bytes, Bytes)> {
+        self.try_next().expect("fail to get value")
+    }
+
+    /// Like [`Iterator::next`], but surfaces a bad value position (a
+    /// truncated data file, a bad CRC, a dangling position after a crash)
+    /// as an `errors::Result` instead of panicking, so iteration over a
+    /// partially corrupted store can be handled instead of aborting the
+    /// process.
+    pub fn try_next(&mut self) -> Result<Option<(Bytes, Bytes)>> {
         let mut index_iter = self.index_iter.write();
-        if let Some(item) = index_iter.next() {
-            let value = self
-                .engine
-                .get_value_by_position(item.1)
-                .expect("fail to get value");
-            return Some((Bytes::from(item.0.to_vec()), value));
+        while let Some(item) = index_iter.next() {
+            if let Some(snapshot_seq) = self.snapshot_seq {
+                if item.1.seq > snapshot_seq {
+                    continue;
+                }
+            }
+            let value = self.engine.get_value_by_position(item.1)?;
+            return Ok(Some((Bytes::from(item.0.to_vec()), value)));
+        }
+        Ok(None)
+    }
+}
+
+impl std::iter::Iterator for Iterator<'_> {
+    type Item = Result<(Bytes, Bytes)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.try_next() {
+            Ok(Some(item)) => Some(Ok(item)),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
         }
-        None
     }
 }
 
@@ -184,6 +306,27 @@ mod tests {
         std::fs::remove_dir_all(opts.clone().dir_path).expect("failed to remove dir");
     }
 
+    #[test]
+    fn test_std_iterator() {
+        let mut opts = Options::default();
+        opts.dir_path = PathBuf::from("/tmp/bitcask-rs-iter-std-iterator");
+        let engine = Engine::open(opts.clone()).expect("failed to open engine");
+
+        let put_res1 = engine.put(Bytes::from("aacc"), util::rand_kv::get_test_value(10));
+        assert!(put_res1.is_ok());
+        let put_res2 = engine.put(Bytes::from("eecc"), util::rand_kv::get_test_value(10));
+        assert!(put_res2.is_ok());
+
+        let iter = engine.iter(IteratorOptions::default());
+        let keys: Vec<Bytes> = iter
+            .filter_map(|item| item.ok())
+            .map(|(key, _)| key)
+            .collect();
+        assert_eq!(keys.len(), 2);
+
+        std::fs::remove_dir_all(opts.clone().dir_path).expect("failed to remove dir");
+    }
+
     #[test]
     fn test_prefix() {
         let mut opts = Options::default();
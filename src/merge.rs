@@ -3,40 +3,84 @@ use crate::data::data_file::{get_data_file_name, DataFile, DATA_FILE_NAME_SUFFIX
 use crate::data::log_record::{decode_log_record_pos, LogRecodType, LogRecord};
 use crate::db::{Engine, FILE_LOCK_NAME};
 use crate::errors::{Errors, Result};
-use crate::options::{IOType, Options};
-use log::error;
+use crate::options::{IOType, MergeOptions, Options};
+use log::{error, info};
+use parking_lot::{Condvar, Mutex};
 use std::fs;
 use std::path::PathBuf;
 use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::thread;
 use crate::util;
 
 const MERGE_DIR_NAME: &str = "merge";
 const MERGE_FIN_KEY: &[u8] = "merge-finshed".as_bytes();
 impl Engine {
-    pub fn merge(&mut self) -> Result<()> {
+    /// Rewrite every data file whose own garbage ratio clears
+    /// `Options::data_file_merge_ratio`. Equivalent to
+    /// `merge_with(MergeOptions::default())`, an unbounded pass.
+    pub fn merge(&self) -> Result<()> {
+        self.merge_with(MergeOptions::default())
+    }
+
+    /// Like [`Engine::merge`], but bounded by `merge_opts` so a large,
+    /// garbage-heavy dataset can be compacted across several calls instead
+    /// of needing the whole keyspace to fit rewritten twice over on disk in
+    /// one pass.
+    ///
+    /// Candidate files are selected in write (file-id) order rather than
+    /// sorted worst-ratio-first: a merge pass writes its rewritten records
+    /// into a fresh run of data files starting back at id 0, which is only
+    /// safe once every file below the merge boundary has actually been
+    /// deleted, so a contiguous prefix is the only selection that can't
+    /// collide with a file this pass chose to leave behind. Selection
+    /// walks that prefix outward from the oldest file and stops at the
+    /// first file whose ratio doesn't clear `data_file_merge_ratio` or
+    /// once `merge_opts.max_files`/`max_bytes` is reached, so a later call
+    /// picks back up exactly where this one left off.
+    pub fn merge_with(&self, merge_opts: MergeOptions) -> Result<()> {
         if self.is_empty_engine() {
             return Ok(());
         }
 
+        // A merge pass itself only rewrites already-superseded records away
+        // from files this process still has open, and leaves `self.index`
+        // untouched; the old files are only actually deleted (and the index
+        // repointed at the merged ones) by `load_merge_files` on the next
+        // `Engine::open`, which no currently-open `Snapshot` can live to
+        // see. So this check doesn't guard against an exploitable bug
+        // today, but it keeps that safety margin explicit and enforced
+        // rather than an accidental byproduct of how merge happens to
+        // sequence its work, in case merge is ever changed to reclaim
+        // space within the same process.
+        if let Some(seq) = self.snapshots.min_live_seq() {
+            return Err(Errors::SnapshotOpen(seq));
+        }
+
         let lock = self.merging_lock.try_lock();
 
         if lock.is_none() {
             return Err(Errors::MergingIsProgressing);
         }
 
-        let reclaim_size=self.reclaim_size.load(Ordering::SeqCst);
-        let total_size=util::file::dir_disk_size(self.option.dir_path.clone());
-
-        if (reclaim_size as f32/total_size as f32) < self.option.data_file_merge_ratio {
+        let merge_files = self.select_merge_files(&merge_opts)?;
+        if merge_files.is_empty() {
             return Err(Errors::CanNotMerge);
         }
 
-        let avaible_size=util::file::available_disk_size();
-        if total_size-reclaim_size as u64>=avaible_size {
+        let candidate_size: u64 = merge_files.iter().map(|f| f.file_size()).sum();
+        let candidate_garbage: u64 = {
+            let file_garbage = self.file_garbage.read();
+            merge_files
+                .iter()
+                .map(|f| *file_garbage.get(&f.get_file_id()).unwrap_or(&0) as u64)
+                .sum()
+        };
+        let avaible_size = util::file::available_disk_size(self.option.dir_path.clone());
+        if candidate_size.saturating_sub(candidate_garbage) >= avaible_size {
             return Err(Errors::NoEnoughDiskCapacity);
         }
 
-        let merge_files = self.rotate_merge_file()?;
         let merge_path = get_merge_path(self.option.dir_path.clone());
         if merge_path.is_dir() {
             fs::remove_dir_all(merge_path.clone()).unwrap();
@@ -45,7 +89,6 @@ impl Engine {
             error!("Failed to create merge directory {}", e);
             return Err(Errors::FailToCreateDatabaseDir);
         }
-        let merge_files = self.rotate_merge_file()?;
 
         let mut merge_db_opts = Options::default();
         merge_db_opts.dir_path = merge_path.clone();
@@ -93,6 +136,14 @@ impl Engine {
         merge_fin_file.write(&enc_record)?;
         merge_fin_file.sync()?;
 
+        self.file_garbage
+            .write()
+            .retain(|file_id, _| *file_id >= non_merge_files_id);
+
+        self.rebuild_bloom_filter();
+        self.write_checkpoint()?;
+        self.write_manifest()?;
+
         Ok(())
     }
 
@@ -102,12 +153,14 @@ impl Engine {
         active_file.get_write_off()==0 && older_file.len()==0
     }
 
-    fn rotate_merge_file(&self) -> Result<Vec<DataFile>> {
-        let mut merge_file_ids = Vec::new();
-        let mut older_files = self.older_file.write();
-        for fid in older_files.keys() {
-            merge_file_ids.push(*fid);
-        }
+    /// Rotate the active file out (so new writes can't land in a file this
+    /// pass is about to rewrite), then select the contiguous run of oldest
+    /// older files whose own garbage ratio clears
+    /// `Options::data_file_merge_ratio`, bounded by `merge_opts`. See
+    /// [`Engine::merge_with`] for why the selection has to stay a
+    /// contiguous prefix instead of picking the worst files regardless of
+    /// position.
+    fn select_merge_files(&self, merge_opts: &MergeOptions) -> Result<Vec<DataFile>> {
         let mut active_file = self.active_file.write();
         active_file.sync()?;
         let active_file_id = active_file.get_file_id();
@@ -117,25 +170,76 @@ impl Engine {
             IOType::StandardIO,
         )?;
         *active_file = new_file;
+        drop(active_file);
 
         let old_file = DataFile::new(
             self.option.dir_path.clone(),
             active_file_id,
             IOType::StandardIO,
         )?;
+        let mut older_files = self.older_file.write();
         older_files.insert(active_file_id, old_file);
-        merge_file_ids.push(active_file_id);
-        merge_file_ids.sort();
+
+        let mut file_ids: Vec<u32> = older_files.keys().copied().collect();
+        file_ids.sort();
+
+        let file_garbage = self.file_garbage.read();
+        let mut selected_ids = Vec::new();
+        let mut total_bytes = 0u64;
+        for file_id in file_ids {
+            if let Some(max_files) = merge_opts.max_files {
+                if selected_ids.len() >= max_files {
+                    break;
+                }
+            }
+            let file_size = older_files.get(&file_id).unwrap().file_size();
+            let garbage = *file_garbage.get(&file_id).unwrap_or(&0) as u64;
+            let ratio = if file_size == 0 {
+                0.0
+            } else {
+                garbage as f32 / file_size as f32
+            };
+            if ratio < self.option.data_file_merge_ratio {
+                break;
+            }
+            if let Some(max_bytes) = merge_opts.max_bytes {
+                if !selected_ids.is_empty() && total_bytes + file_size > max_bytes {
+                    break;
+                }
+            }
+            total_bytes += file_size;
+            selected_ids.push(file_id);
+        }
+        drop(file_garbage);
+        drop(older_files);
 
         let mut merge_files = Vec::new();
-        for file_id in merge_file_ids.iter() {
+        for file_id in selected_ids {
             let data_file =
-                DataFile::new(self.option.dir_path.clone(), *file_id, IOType::StandardIO)?;
+                DataFile::new(self.option.dir_path.clone(), file_id, IOType::StandardIO)?;
             merge_files.push(data_file);
         }
         Ok(merge_files)
     }
 
+    /// Spawn a background thread that periodically checks whether this
+    /// engine has accumulated enough reclaimable space, or the filesystem
+    /// hosting `dir_path` has run low on room, to be worth compacting, and
+    /// calls [`Engine::merge`] when it has.
+    ///
+    /// Requires the caller to already hold the engine behind an `Arc`
+    /// (mirroring how [`crate::async_engine::AsyncEngine`] wraps a plain
+    /// `Engine`), since the thread needs a handle that outlives this call.
+    /// Moving that `Arc<Engine>` into the spawned thread requires
+    /// `Engine: Send`, which in turn requires `Indexer: Send + Sync` (see
+    /// `crate::index::Indexer`) — without that bound this doesn't compile.
+    /// Returns `None` without starting a thread if
+    /// `Options::auto_merge_check_interval` is zero. Drop the returned
+    /// [`MergeScheduler`] to stop the thread.
+    pub fn start_merge_scheduler(self: &Arc<Self>) -> Option<MergeScheduler> {
+        MergeScheduler::start(self.clone())
+    }
+
     pub(crate) fn load_index_from_hint_files(&self) -> Result<()> {
         let hint_file_name = self.option.dir_path.join(HINT_FILE_NAME);
         if !hint_file_name.is_file() {
@@ -164,6 +268,88 @@ impl Engine {
     }
 }
 
+/// Background thread handle returned by [`Engine::start_merge_scheduler`].
+///
+/// Dropping it signals the thread to stop and blocks until it has exited,
+/// so a caller that embeds this in a longer-lived struct gets a clean
+/// shutdown for free.
+pub struct MergeScheduler {
+    stop: Arc<(Mutex<bool>, Condvar)>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl MergeScheduler {
+    fn start(engine: Arc<Engine>) -> Option<Self> {
+        let interval = engine.option.auto_merge_check_interval;
+        if interval.is_zero() {
+            return None;
+        }
+
+        let stop = Arc::new((Mutex::new(false), Condvar::new()));
+        let stop_handle = stop.clone();
+        let thread = thread::spawn(move || {
+            let (lock, cv) = &*stop_handle;
+            loop {
+                let mut stopped = lock.lock();
+                let _ = cv.wait_for(&mut stopped, interval);
+                if *stopped {
+                    return;
+                }
+                drop(stopped);
+
+                if let Err(e) = check_and_merge(&engine) {
+                    error!("auto merge scheduler: merge failed: {}", e);
+                }
+            }
+        });
+
+        Some(MergeScheduler {
+            stop,
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for MergeScheduler {
+    fn drop(&mut self) {
+        *self.stop.0.lock() = true;
+        self.stop.1.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Sample disk usage and run a merge if either threshold is crossed.
+///
+/// `Engine::merge` already refuses to run (returning `Errors::CanNotMerge`
+/// or `Errors::NoEnoughDiskCapacity`) when it isn't worth it or wouldn't
+/// fit, so this only needs to decide whether it's worth *asking*; a
+/// `CanNotMerge` here (e.g. another check tipped the ratio but a concurrent
+/// merge already reclaimed the space) is not an error worth reporting.
+fn check_and_merge(engine: &Engine) -> Result<()> {
+    let reclaim_size = engine.reclaim_size.load(Ordering::SeqCst) as u64;
+    let total_size = util::file::dir_disk_size(engine.option.dir_path.clone());
+    let ratio_crossed =
+        total_size > 0 && reclaim_size as f32 / total_size as f32 >= engine.option.data_file_merge_ratio;
+
+    let available = util::file::available_disk_size(engine.option.dir_path.clone());
+    let low_on_space = available < engine.option.auto_merge_min_free_space;
+
+    if !ratio_crossed && !low_on_space {
+        return Ok(());
+    }
+
+    match engine.merge() {
+        Ok(()) => {
+            info!("auto merge scheduler: merge completed");
+            Ok(())
+        }
+        Err(Errors::CanNotMerge) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
 fn get_merge_path(dir_path: PathBuf) -> PathBuf {
     let file_name = dir_path.file_name().unwrap();
     let merge_name = std::format!("{}-{}", file_name.to_str().unwrap(), MERGE_DIR_NAME);
@@ -1,4 +1,5 @@
 use std::path::PathBuf;
+use std::time::Duration;
 #[derive(Clone, Debug)]
 pub struct Options {
     pub dir_path: PathBuf,
@@ -8,6 +9,51 @@ pub struct Options {
     pub index_type: IndexType,
     pub mmap_at_startup: bool,
     pub data_file_merge_ratio:f32,
+    pub compression: CompressionType,
+    pub bloom_filter_expected_keys: usize,
+    pub bloom_filter_fp_rate: f64,
+    /// Capacity, in bytes, of the sharded LRU cache of decoded log-record
+    /// values kept at the engine read layer. `0` disables the cache.
+    pub cache_capacity_bytes: usize,
+    /// Maximum number of older (read-only) data files kept open at once.
+    ///
+    /// Every data file beyond this is closed on an LRU basis by
+    /// [`crate::fio::fd_cache::FdCache`] and transparently reopened the next
+    /// time it is read, so random reads over a large dataset do not exhaust
+    /// `RLIMIT_NOFILE`. The active (writable) file is always kept open.
+    pub fd_cache_capacity: usize,
+    /// How often [`crate::merge::MergeScheduler`] samples disk usage to
+    /// decide whether a merge is worth running. `Duration::ZERO` disables
+    /// the scheduler entirely.
+    pub auto_merge_check_interval: Duration,
+    /// Free-space reserve, in bytes, on the filesystem hosting `dir_path`.
+    ///
+    /// If sampled free space drops below this, [`crate::merge::MergeScheduler`]
+    /// triggers a merge even if `data_file_merge_ratio` hasn't been crossed,
+    /// since a merge reclaims space that stale/deleted records are holding
+    /// onto.
+    pub auto_merge_min_free_space: u64,
+    /// Overrides the automatic network-filesystem detection (see
+    /// [`crate::util::file::is_network_filesystem`]) that otherwise decides
+    /// whether `mmap_at_startup` is honored. `Some(IOType::StandardIO)`
+    /// forces standard I/O even on a local disk; `Some(IOType::MemoryMap)`
+    /// forces mmap even if `dir_path` looks like a network mount. `None`
+    /// (the default) leaves the heuristic in charge.
+    pub force_io_type: Option<IOType>,
+}
+
+/// Codec applied to a log record's value before it is written to disk.
+///
+/// `None` stores the value as-is. The other variants compress the value and
+/// fall back to storing it uncompressed when compression does not actually
+/// shrink it, so a single data file can freely mix compressed and
+/// uncompressed records.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CompressionType {
+    None = 0,
+    Lz4 = 1,
+    Snappy = 2,
+    Zstd = 3,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -29,6 +75,14 @@ impl Default for Options {
             index_type: IndexType::BTree,
             mmap_at_startup: true,
             data_file_merge_ratio: 0.5,
+            compression: CompressionType::None,
+            bloom_filter_expected_keys: 1_000_000,
+            bloom_filter_fp_rate: 0.01,
+            cache_capacity_bytes: 64 * 1024 * 1024,
+            fd_cache_capacity: 1024,
+            auto_merge_check_interval: Duration::ZERO,
+            auto_merge_min_free_space: 512 * 1024 * 1024,
+            force_io_type: None,
         }
     }
 }
@@ -36,6 +90,12 @@ impl Default for Options {
 pub struct IteratorOptions {
     pub prefix: Vec<u8>,
     pub reverse: bool,
+    /// Exclusive bound at which iteration stops: once a key reaches it
+    /// (greater-or-equal going forward, less-or-equal going in reverse),
+    /// the iterator reports `None` instead of continuing past it. `None`
+    /// means unbounded, matching the prior behavior of scanning to the end
+    /// of the keyspace (or of `prefix`).
+    pub end: Option<Vec<u8>>,
 }
 
 impl Default for IteratorOptions {
@@ -43,6 +103,7 @@ impl Default for IteratorOptions {
         Self {
             prefix: Default::default(),
             reverse: false,
+            end: None,
         }
     }
 }
@@ -65,3 +126,15 @@ pub enum IOType {
     StandardIO,
     MemoryMap,
 }
+
+/// Bounds a single [`crate::db::Engine::merge_with`] pass so a large,
+/// garbage-heavy dataset can be compacted incrementally across several
+/// calls instead of requiring the whole keyspace to fit rewritten twice
+/// over on disk in one shot. `None` means unbounded, matching
+/// `Engine::merge`'s behavior of compacting every file that clears
+/// `Options::data_file_merge_ratio`.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct MergeOptions {
+    pub max_files: Option<usize>,
+    pub max_bytes: Option<u64>,
+}
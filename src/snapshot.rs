@@ -0,0 +1,64 @@
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Tracks which write-sequence numbers are still visible to an open
+/// [`Snapshot`], so callers that want to reason about compaction safety can
+/// check whether reclaiming a given version would break an in-flight read.
+///
+/// Entries are reference-counted since two snapshots can legitimately share
+/// the same sequence number (e.g. back-to-back snapshots with no writes in
+/// between).
+#[derive(Default)]
+pub(crate) struct SnapshotRegistry {
+    live: Mutex<BTreeMap<u64, usize>>,
+}
+
+impl SnapshotRegistry {
+    pub(crate) fn register(&self, seq: u64) {
+        *self.live.lock().entry(seq).or_insert(0) += 1;
+    }
+
+    fn unregister(&self, seq: u64) {
+        let mut live = self.live.lock();
+        if let Some(count) = live.get_mut(&seq) {
+            *count -= 1;
+            if *count == 0 {
+                live.remove(&seq);
+            }
+        }
+    }
+
+    /// The lowest sequence number still visible to an open snapshot, if any.
+    pub(crate) fn min_live_seq(&self) -> Option<u64> {
+        self.live.lock().keys().next().copied()
+    }
+}
+
+/// A point-in-time view of the database: reads through a `Snapshot` only see
+/// writes whose sequence number is at most `seq`.
+///
+/// Because the in-memory index keeps only the newest `LogRecodPos` per key,
+/// a snapshot can only serve a key whose value has not been overwritten
+/// since the snapshot was taken; see `Engine::get_at`.
+pub struct Snapshot {
+    pub(crate) seq: u64,
+    registry: Arc<SnapshotRegistry>,
+}
+
+impl Snapshot {
+    pub(crate) fn new(seq: u64, registry: Arc<SnapshotRegistry>) -> Self {
+        registry.register(seq);
+        Snapshot { seq, registry }
+    }
+
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+}
+
+impl Drop for Snapshot {
+    fn drop(&mut self) {
+        self.registry.unregister(self.seq);
+    }
+}
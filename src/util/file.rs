@@ -7,9 +7,106 @@ pub fn dir_disk_size(dir_path:PathBuf)->u64 {
     0
 }
 
-pub fn available_disk_size()->u64{
-    if let Ok(size)=fs2::available_space(PathBuf::from("/")){
+pub fn available_disk_size(dir_path: PathBuf) -> u64 {
+    if let Ok(size) = fs2::available_space(dir_path) {
         return size;
     }
     0
+}
+
+/// `f_type` magic numbers (from `statfs(2)`) for filesystems backed by a
+/// network share rather than local storage, where mmap can hand back stale
+/// or torn pages behind the engine's back.
+#[cfg(target_os = "linux")]
+const NETWORK_FILESYSTEM_MAGICS: &[i64] = &[
+    0x6969,     // NFS_SUPER_MAGIC
+    0xFF534D42, // CIFS_MAGIC_NUMBER / SMB2
+    0x517B,     // SMB_SUPER_MAGIC
+    0x65735546, // FUSE_SUPER_MAGIC (sshfs, most other FUSE network mounts)
+    0x19830326, // FHGFS/BEEGFS
+];
+
+/// Whether `dir_path` lives on a filesystem backed by a network share.
+///
+/// Used to force `IOType::StandardIO` regardless of `Options::mmap_at_startup`,
+/// since mmap over NFS/CIFS/FUSE is unsafe and can produce stale or torn
+/// reads. Returns `false` (i.e. assumes local storage) if the check can't be
+/// performed, since that's the existing, already-exercised behavior.
+#[cfg(target_os = "linux")]
+pub fn is_network_filesystem(dir_path: &std::path::Path) -> bool {
+    let c_path = match std::ffi::CString::new(dir_path.as_os_str().as_encoded_bytes()) {
+        Ok(c_path) => c_path,
+        Err(_) => return false,
+    };
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return false;
+    }
+    NETWORK_FILESYSTEM_MAGICS.contains(&(stat.f_type as i64))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn is_network_filesystem(_dir_path: &std::path::Path) -> bool {
+    false
+}
+
+/// Raise the process's soft `RLIMIT_NOFILE` as close to the hard limit as
+/// the platform allows.
+///
+/// A Bitcask-style store can accumulate a large number of immutable data
+/// files; even with the bounded [`crate::fio::fd_cache::FdCache`] keeping a
+/// lid on how many stay open at once, a generous descriptor budget gives the
+/// cache more room before it has to start evicting. Failures here are not
+/// fatal: if the limit can't be read or raised (e.g. on a platform without
+/// `getrlimit`/`setrlimit`, or because of a hardened sandbox) the engine
+/// just keeps whatever limit it started with.
+#[cfg(unix)]
+pub fn raise_fd_limit() {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) } != 0 {
+        return;
+    }
+
+    let mut target = limit.rlim_max;
+    #[cfg(target_os = "macos")]
+    {
+        target = clamp_to_maxfilesperproc(target);
+    }
+
+    if target <= limit.rlim_cur {
+        return;
+    }
+
+    limit.rlim_cur = target;
+    unsafe {
+        libc::setrlimit(libc::RLIMIT_NOFILE, &limit);
+    }
+}
+
+#[cfg(not(unix))]
+pub fn raise_fd_limit() {}
+
+/// macOS refuses to `setrlimit` a `rlim_cur` above `kern.maxfilesperproc`,
+/// even when `rlim_max` (often `RLIM_INFINITY`) suggests otherwise.
+#[cfg(target_os = "macos")]
+fn clamp_to_maxfilesperproc(target: libc::rlim_t) -> libc::rlim_t {
+    let mut max_per_proc: libc::c_int = 0;
+    let mut size = std::mem::size_of::<libc::c_int>();
+    let name = std::ffi::CString::new("kern.maxfilesperproc").unwrap();
+    let ret = unsafe {
+        libc::sysctlbyname(
+            name.as_ptr(),
+            &mut max_per_proc as *mut _ as *mut libc::c_void,
+            &mut size,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    if ret == 0 && max_per_proc > 0 {
+        return target.min(max_per_proc as libc::rlim_t);
+    }
+    target
 }
\ No newline at end of file